@@ -30,6 +30,7 @@ func main() {
 	module.Set("transform", Transform())
 	module.Set("parse", Parse())
 	module.Set("convertToTSX", ConvertToTSX())
+	module.Set("transformCSS", TransformCSS())
 
 	<-make(chan struct{})
 }
@@ -48,6 +49,32 @@ func jsBool(j js.Value) bool {
 	return j.Bool()
 }
 
+// Copies a plain object of strings, like `{ scss: 'sass' }`. Anything else is an empty map.
+func jsStringMap(j js.Value) map[string]string {
+	values := make(map[string]string)
+	if j.Type() != js.TypeObject {
+		return values
+	}
+	keys := js.Global().Get("Object").Call("keys", j)
+	for i := 0; i < keys.Length(); i++ {
+		key := keys.Index(i).String()
+		values[key] = jsString(j.Get(key))
+	}
+	return values
+}
+
+// Copies an array of strings, like `['fallback']`. Anything else is an empty slice.
+func jsStringArray(j js.Value) []string {
+	values := make([]string, 0)
+	if j.Type() != js.TypeObject || !js.Global().Get("Array").Call("isArray", j).Bool() {
+		return values
+	}
+	for i := 0; i < j.Length(); i++ {
+		values = append(values, jsString(j.Index(i)))
+	}
+	return values
+}
+
 func makeParseOptions(options js.Value) t.ParseOptions {
 	position := true
 
@@ -61,6 +88,34 @@ func makeParseOptions(options js.Value) t.ParseOptions {
 	}
 }
 
+// Every accepted `sourcemap` value is listed here, anything else disables sourcemaps
+func makeSourceMapOption(value string) string {
+	switch value {
+	case "<boolean: true>":
+		return "both"
+	case "both", "inline", "external":
+		return value
+	default:
+		return ""
+	}
+}
+
+// Describes an unsupported `sourcemap` value, or returns an empty string
+func sourceMapOptionError(value js.Value) string {
+	const expected = `expected true, "both", "inline" or "external"`
+	switch value.Type() {
+	case js.TypeUndefined, js.TypeNull, js.TypeBoolean:
+		return ""
+	case js.TypeString:
+		if value.String() == "" || makeSourceMapOption(value.String()) != "" {
+			return ""
+		}
+		return fmt.Sprintf("Invalid sourcemap option %q, %s", value.String(), expected)
+	default:
+		return fmt.Sprintf("Invalid sourcemap option of type %s, %s", value.Type(), expected)
+	}
+}
+
 func makeTransformOptions(options js.Value, hash string) transform.TransformOptions {
 	filename := jsString(options.Get("sourcefile"))
 	if filename == "" {
@@ -77,10 +132,7 @@ func makeTransformOptions(options js.Value, hash string) transform.TransformOpti
 		internalURL = "astro/internal"
 	}
 
-	sourcemap := jsString(options.Get("sourcemap"))
-	if sourcemap == "<boolean: true>" {
-		sourcemap = "both"
-	}
+	sourcemap := makeSourceMapOption(jsString(options.Get("sourcemap")))
 
 	site := jsString(options.Get("site"))
 	if site == "" {
@@ -102,19 +154,92 @@ func makeTransformOptions(options js.Value, hash string) transform.TransformOpti
 		staticExtraction = true
 	}
 
+	hmr := false
+	if jsBool(options.Get("hmr")) {
+		hmr = true
+	}
+
+	stripEventHandlers := false
+	if jsBool(options.Get("stripEventHandlers")) {
+		stripEventHandlers = true
+	}
+
+	stripSlotComments := true
+	strip := options.Get("stripSlotComments")
+	if !strip.IsNull() && !strip.IsUndefined() {
+		stripSlotComments = strip.Bool()
+	}
+
+	define := jsStringMap(options.Get("define"))
+
+	inlineScriptThreshold := 0
+	if threshold := options.Get("inlineScriptThreshold"); threshold.Type() == js.TypeNumber {
+		inlineScriptThreshold = threshold.Int()
+	}
+
+	validateHTMLNesting := false
+	if jsBool(options.Get("validateHTMLNesting")) {
+		validateHTMLNesting = true
+	}
+
+	disableRuntimeImports := false
+	if jsBool(options.Get("disableRuntimeImports")) {
+		disableRuntimeImports = true
+	}
+
+	jsxImportSource := jsString(options.Get("jsxImportSource"))
+
+	normalizedFilename := jsString(options.Get("normalizedFilename"))
+	if normalizedFilename == "" {
+		if root := jsString(options.Get("root")); root != "" {
+			normalizedFilename = transform.NormalizeFilename(filename, root)
+		}
+	}
+
+	outputExtension := ""
+	if ext := options.Get("outputExtension"); ext.Type() == js.TypeString {
+		if ext.String() == "" {
+			fmt.Printf("%s: outputExtension should not be empty, it has been ignored\n", filename)
+		} else {
+			outputExtension = ext.String()
+		}
+	}
+
 	preprocessStyle := options.Get("preprocessStyle")
 
+	stylePreprocessorMap := jsStringMap(options.Get("stylePreprocessorMap"))
+
+	emitCSSInline := false
+	if jsBool(options.Get("emitCSSInline")) {
+		emitCSSInline = true
+	}
+
+	scopedSlotNames := jsStringArray(options.Get("scopedSlotNames"))
+
 	return transform.TransformOptions{
-		Scope:            hash,
-		Filename:         filename,
-		Pathname:         pathname,
-		InternalURL:      internalURL,
-		SourceMap:        sourcemap,
-		Site:             site,
-		ProjectRoot:      projectRoot,
-		Compact:          compact,
-		PreprocessStyle:  preprocessStyle,
-		StaticExtraction: staticExtraction,
+		Scope:                 hash,
+		Filename:              filename,
+		Pathname:              pathname,
+		InternalURL:           internalURL,
+		SourceMap:             sourcemap,
+		Site:                  site,
+		ProjectRoot:           projectRoot,
+		Compact:               compact,
+		PreprocessStyle:       preprocessStyle,
+		StaticExtraction:      staticExtraction,
+		HMR:                   hmr,
+		StripEventHandlers:    stripEventHandlers,
+		PreserveSlotComments:  !stripSlotComments,
+		Define:                define,
+		InlineScriptThreshold: inlineScriptThreshold,
+		OutputExtension:       outputExtension,
+		ValidateHTMLNesting:   validateHTMLNesting,
+		DisableRuntimeImports: disableRuntimeImports,
+		JSXImportSource:       jsxImportSource,
+		NormalizedFilename:    normalizedFilename,
+		StylePreprocessorMap:  stylePreprocessorMap,
+		EmitCSSInline:         emitCSSInline,
+		ScopedSlotNames:       scopedSlotNames,
 	}
 }
 
@@ -128,10 +253,11 @@ type RawSourceMap struct {
 }
 
 type HoistedScript struct {
-	Code string `js:"code"`
-	Src  string `js:"src"`
-	Type string `js:"type"`
-	Map  string `js:"map"`
+	Code  string            `js:"code"`
+	Src   string            `js:"src"`
+	Type  string            `js:"type"`
+	Map   string            `js:"map"`
+	Attrs map[string]string `js:"attrs"`
 }
 
 type HydratedComponent struct {
@@ -140,23 +266,68 @@ type HydratedComponent struct {
 	ResolvedPath string `js:"resolvedPath"`
 }
 
+type ModuleImport struct {
+	Specifier   string            `js:"specifier"`
+	Attributes  map[string]string `js:"attributes"`
+	IsCSSImport bool              `js:"isCssImport"`
+	IsTypeOnly  bool              `js:"isTypeOnly"`
+}
+
 type ParseResult struct {
 	AST string `js:"ast"`
 }
 
+type TransformCSSResult struct {
+	Code       string   `js:"code"`
+	Errors     []string `js:"errors"`
+	ScopeClass string   `js:"scopeClass"`
+}
+
 type TSXResult struct {
 	Code string `js:"code"`
 	Map  string `js:"map"`
 }
 
+type MetaTag struct {
+	TagType string `js:"tagType"`
+	Key     string `js:"key"`
+	Content string `js:"content"`
+}
+
+type StyleBlock struct {
+	Content          string `js:"content"`
+	Lang             string `js:"lang"`
+	IsGlobal         bool   `js:"isGlobal"`
+	HasDefineVars    bool   `js:"hasDefineVars"`
+	DefineVarsSource string `js:"defineVarsSource"`
+	Preprocessor     string `js:"preprocessor"`
+}
+
 type TransformResult struct {
 	Code                 string              `js:"code"`
 	Map                  string              `js:"map"`
 	Scope                string              `js:"scope"`
+	ScopeClass           string              `js:"scopeClass"`
 	CSS                  []string            `js:"css"`
+	CSSAttrs             []map[string]string `js:"cssAttrs"`
+	StyleBlocks          []StyleBlock        `js:"styleBlocks"`
 	Scripts              []HoistedScript     `js:"scripts"`
 	HydratedComponents   []HydratedComponent `js:"hydratedComponents"`
 	ClientOnlyComponents []HydratedComponent `js:"clientOnlyComponents"`
+	ClientOnlyFrameworks []string            `js:"clientOnlyFrameworks"`
+	CSSHashes            []string            `js:"cssHashes"`
+	ScriptHashes         []string            `js:"scriptHashes"`
+	TemplateHash         string              `js:"templateHash"`
+	CustomElements       []string            `js:"customElements"`
+	CSSVariables         []string            `js:"cssVariables"`
+	AstroAPIUsage        []string            `js:"astroAPIUsage"`
+	MetaTags             []MetaTag           `js:"metaTags"`
+	HasStyles            bool                `js:"hasStyles"`
+	HasScripts           bool                `js:"hasScripts"`
+	ContainsHead         bool                `js:"containsHead"`
+	ComponentName        string              `js:"componentName"`
+	ResolvedImports      map[string]string   `js:"resolvedImports"`
+	Modules              []ModuleImport      `js:"modules"`
 }
 
 // This is spawned as a goroutine to preprocess style nodes using an async function passed from JS
@@ -215,16 +386,57 @@ func ConvertToTSX() interface{} {
 	})
 }
 
+func TransformCSS() interface{} {
+	return js.FuncOf(func(this js.Value, args []js.Value) interface{} {
+		source := jsString(args[0])
+		options := js.Value(args[1])
+
+		result := transform.TransformCSS(source, transform.TransformCSSOptions{
+			Scope:    jsString(options.Get("scope")),
+			Strategy: jsString(options.Get("strategy")),
+		})
+
+		return vert.ValueOf(TransformCSSResult{
+			Code:       result.Code,
+			Errors:     result.Errors,
+			ScopeClass: result.ScopeClass,
+		})
+	})
+}
+
 func Transform() interface{} {
 	return js.FuncOf(func(this js.Value, args []js.Value) interface{} {
 		source := jsString(args[0])
+		// Without a filename the scope only depends on the source, so formatting
+		// changes shouldn't produce a new scope
+		ignoreWhitespace := jsString(js.Value(args[1]).Get("sourcefile")) == ""
 		hash := astro.HashFromSource(source)
+		if ignoreWhitespace {
+			hash = astro.HashFromSourceIgnoringWhitespace(source)
+		}
 		transformOptions := makeTransformOptions(js.Value(args[1]), hash)
+		sourceMapError := sourceMapOptionError(js.Value(args[1]).Get("sourcemap"))
+		parseOnly := jsBool(js.Value(args[1]).Get("parseOnly"))
+		parseOptions := makeParseOptions(js.Value(args[1]))
 
 		handler := js.FuncOf(func(this js.Value, args []js.Value) interface{} {
 			resolve := args[0]
+			reject := args[1]
 
 			go func() {
+				// A panic would otherwise take down the whole service, reject
+				// this transform instead so the next one still works
+				defer func() {
+					if r := recover(); r != nil {
+						reject.Invoke(js.Global().Get("Error").New(fmt.Sprintf("%s: %v", transformOptions.Filename, r)))
+					}
+				}()
+
+				if sourceMapError != "" {
+					reject.Invoke(js.Global().Get("Error").New(fmt.Sprintf("%s: %s", transformOptions.Filename, sourceMapError)))
+					return
+				}
+
 				var doc *astro.Node
 
 				doc, err := astro.Parse(strings.NewReader(source))
@@ -232,11 +444,29 @@ func Transform() interface{} {
 					fmt.Println(err)
 				}
 
+				// Skip every transform and return the same AST as `parse`
+				if parseOnly {
+					result := printer.PrintToJSON(source, doc, parseOptions)
+					resolve.Invoke(vert.ValueOf(TransformResult{
+						Code:  string(result.Output),
+						Scope: transformOptions.Scope,
+					}))
+					return
+				}
+
+				if err := transform.FindFetchContentError(doc); err != "" {
+					reject.Invoke(js.Global().Get("Error").New(fmt.Sprintf("%s: %s", transformOptions.Filename, err)))
+					return
+				}
+
 				// Hoist styles and scripts to the top-level
-				transform.ExtractStyles(doc)
+				transform.ExtractStyles(doc, &transformOptions)
 
 				if len(doc.Styles) > 0 {
 					newHash := astro.HashFromDoc(doc)
+					if ignoreWhitespace {
+						newHash = astro.HashFromDocIgnoringWhitespace(doc)
+					}
 					transformOptions.Scope = newHash
 				}
 
@@ -258,25 +488,58 @@ func Transform() interface{} {
 				// Perform CSS and element scoping as needed
 				transform.Transform(doc, transformOptions)
 
+				for _, m := range transform.FindMixedHydration(doc) {
+					if m.IsError() {
+						reject.Invoke(js.Global().Get("Error").New(fmt.Sprintf("%s: %s", transformOptions.Filename, m.Message(source))))
+						return
+					}
+					fmt.Printf("%s: %s\n", transformOptions.Filename, m.Message(source))
+				}
+				for _, d := range transform.FindDuplicateDocumentElements(doc) {
+					fmt.Printf("%s: %s\n", transformOptions.Filename, d.Message(source))
+				}
+
 				css := []string{}
+				cssAttrs := []map[string]string{}
+				styleBlocks := []StyleBlock{}
 				scripts := []HoistedScript{}
 				hydratedComponents := []HydratedComponent{}
 				clientOnlyComponents := []HydratedComponent{}
 				// Only perform static CSS extraction if the flag is passed in.
 				if transformOptions.StaticExtraction {
-					css_result := printer.PrintCSS(source, doc, transformOptions)
-					for _, bytes := range css_result.Output {
-						css = append(css, string(bytes))
+					// Inline styles are part of the code, so nothing is extracted
+					if !transformOptions.EmitCSSInline {
+						css_result := printer.PrintCSS(source, doc, transformOptions)
+						for _, bytes := range css_result.Output {
+							css = append(css, string(bytes))
+						}
+						for i, style := range css_result.Styles {
+							cssAttrs = append(cssAttrs, wasm_utils.GetAttrsMap(style))
+							defineVars := transform.GetDefineVarsSource(style)
+							lang := ""
+							if attr := transform.GetAttr(style, "lang"); attr != nil {
+								lang = attr.Val
+							}
+							styleBlocks = append(styleBlocks, StyleBlock{
+								Content:          css[i],
+								Lang:             lang,
+								IsGlobal:         transform.IsGlobalStyle(style),
+								HasDefineVars:    defineVars != "",
+								DefineVarsSource: defineVars,
+								Preprocessor:     transformOptions.StylePreprocessorMap[lang],
+							})
+						}
 					}
 
 					// Append hoisted scripts
 					for _, node := range doc.Scripts {
 						src := astro.GetAttribute(node, "src")
 						script := HoistedScript{
-							Src:  "",
-							Code: "",
-							Type: "",
-							Map:  "",
+							Src:   "",
+							Code:  "",
+							Type:  "",
+							Map:   "",
+							Attrs: wasm_utils.GetAttrsMap(node),
 						}
 
 						if src != nil {
@@ -347,27 +610,89 @@ func Transform() interface{} {
 
 				result := printer.PrintToJS(source, doc, len(css), transformOptions)
 
-				var value interface{}
+				cssHashes := make([]string, 0)
+				for _, c := range css {
+					cssHashes = append(cssHashes, astro.HashFromSource(c))
+				}
+				scriptHashes := make([]string, 0)
+				for _, script := range scripts {
+					if script.Type == "external" {
+						scriptHashes = append(scriptHashes, astro.HashFromSource(script.Src))
+					} else {
+						scriptHashes = append(scriptHashes, astro.HashFromSource(script.Code))
+					}
+				}
+
+				clientOnlyFrameworks := make([]string, 0)
+				clientOnlyFrameworks = append(clientOnlyFrameworks, doc.ClientOnlyFrameworks...)
+				customElements := make([]string, 0)
+				customElements = append(customElements, doc.CustomElements...)
+				cssVariables := make([]string, 0)
+				cssVariables = append(cssVariables, doc.CSSVariables...)
+				astroAPIUsage := make([]string, 0)
+				astroAPIUsage = append(astroAPIUsage, doc.AstroAPIUsage...)
+				resolvedImports := make(map[string]string)
+				for specifier, resolved := range doc.ResolvedImports {
+					resolvedImports[specifier] = resolved
+				}
+				modules := make([]ModuleImport, 0)
+				for _, m := range result.Modules {
+					modules = append(modules, ModuleImport{
+						Specifier:   m.Specifier,
+						Attributes:  m.Attributes,
+						IsCSSImport: m.IsCSSImport,
+						IsTypeOnly:  m.IsTypeOnly,
+					})
+				}
+				metaTags := make([]MetaTag, 0)
+				for _, m := range doc.MetaTags {
+					metaTags = append(metaTags, MetaTag{
+						TagType: m.TagType,
+						Key:     m.Key,
+						Content: m.Content,
+					})
+				}
+
+				transformResult := TransformResult{
+					CSS:                  css,
+					CSSAttrs:             cssAttrs,
+					StyleBlocks:          styleBlocks,
+					Code:                 string(result.Output),
+					Map:                  "",
+					Scope:                transformOptions.Scope,
+					ScopeClass:           transform.ScopeClass(transformOptions.Scope, ""),
+					Scripts:              scripts,
+					HydratedComponents:   hydratedComponents,
+					ClientOnlyComponents: clientOnlyComponents,
+					ClientOnlyFrameworks: clientOnlyFrameworks,
+					CSSHashes:            cssHashes,
+					ScriptHashes:         scriptHashes,
+					TemplateHash:         result.TemplateHash,
+					CustomElements:       customElements,
+					CSSVariables:         cssVariables,
+					AstroAPIUsage:        astroAPIUsage,
+					MetaTags:             metaTags,
+					HasStyles:            len(doc.Styles) > 0,
+					HasScripts:           len(doc.Scripts) > 0,
+					ContainsHead:         doc.ContainsHead,
+					ComponentName:        result.ComponentName,
+					ResolvedImports:      resolvedImports,
+					Modules:              modules,
+				}
+
 				switch transformOptions.SourceMap {
 				case "external":
-					value = createExternalSourceMap(source, result, css, &scripts, &hydratedComponents, &clientOnlyComponents, transformOptions)
+					transformResult.Map = createSourceMapString(source, result, transformOptions)
 				case "both":
-					value = createBothSourceMap(source, result, css, &scripts, &hydratedComponents, &clientOnlyComponents, transformOptions)
+					sourcemapString := createSourceMapString(source, result, transformOptions)
+					transformResult.Code += "\n" + createInlineSourceMapComment(sourcemapString)
+					transformResult.Map = sourcemapString
 				case "inline":
-					value = createInlineSourceMap(source, result, css, &scripts, &hydratedComponents, &clientOnlyComponents, transformOptions)
-				default:
-					value = vert.ValueOf(TransformResult{
-						CSS:                  css,
-						Code:                 string(result.Output),
-						Map:                  "",
-						Scope:                transformOptions.Scope,
-						Scripts:              scripts,
-						HydratedComponents:   hydratedComponents,
-						ClientOnlyComponents: clientOnlyComponents,
-					})
+					sourcemapString := createSourceMapString(source, result, transformOptions)
+					transformResult.Code += "\n" + createInlineSourceMapComment(sourcemapString)
 				}
 
-				resolve.Invoke(value)
+				resolve.Invoke(vert.ValueOf(transformResult))
 			}()
 
 			return nil
@@ -397,42 +722,6 @@ func createSourceMapString(source string, result printer.PrintResult, transformO
 }`, sourcemap.Sources[0], sourcemap.SourcesContent[0], sourcemap.Mappings)
 }
 
-func createExternalSourceMap(source string, result printer.PrintResult, css []string, scripts *[]HoistedScript, hydratedComponents *[]HydratedComponent, clientOnlyComponents *[]HydratedComponent, transformOptions transform.TransformOptions) interface{} {
-	return vert.ValueOf(TransformResult{
-		CSS:                  css,
-		Code:                 string(result.Output),
-		Map:                  createSourceMapString(source, result, transformOptions),
-		Scope:                transformOptions.Scope,
-		Scripts:              *scripts,
-		HydratedComponents:   *hydratedComponents,
-		ClientOnlyComponents: *clientOnlyComponents,
-	})
-}
-
-func createInlineSourceMap(source string, result printer.PrintResult, css []string, scripts *[]HoistedScript, hydratedComponents *[]HydratedComponent, clientOnlyComponents *[]HydratedComponent, transformOptions transform.TransformOptions) interface{} {
-	sourcemapString := createSourceMapString(source, result, transformOptions)
-	inlineSourcemap := `//# sourceMappingURL=data:application/json;charset=utf-8;base64,` + base64.StdEncoding.EncodeToString([]byte(sourcemapString))
-	return vert.ValueOf(TransformResult{
-		CSS:                  css,
-		Code:                 string(result.Output) + "\n" + inlineSourcemap,
-		Map:                  "",
-		Scope:                transformOptions.Scope,
-		Scripts:              *scripts,
-		HydratedComponents:   *hydratedComponents,
-		ClientOnlyComponents: *clientOnlyComponents,
-	})
-}
-
-func createBothSourceMap(source string, result printer.PrintResult, css []string, scripts *[]HoistedScript, hydratedComponents *[]HydratedComponent, clientOnlyComponents *[]HydratedComponent, transformOptions transform.TransformOptions) interface{} {
-	sourcemapString := createSourceMapString(source, result, transformOptions)
-	inlineSourcemap := `//# sourceMappingURL=data:application/json;charset=utf-8;base64,` + base64.StdEncoding.EncodeToString([]byte(sourcemapString))
-	return vert.ValueOf(TransformResult{
-		CSS:                  css,
-		Code:                 string(result.Output) + "\n" + inlineSourcemap,
-		Map:                  sourcemapString,
-		Scope:                transformOptions.Scope,
-		Scripts:              *scripts,
-		HydratedComponents:   *hydratedComponents,
-		ClientOnlyComponents: *clientOnlyComponents,
-	})
+func createInlineSourceMapComment(sourcemapString string) string {
+	return `//# sourceMappingURL=data:application/json;charset=utf-8;base64,` + base64.StdEncoding.EncodeToString([]byte(sourcemapString))
 }
@@ -48,7 +48,7 @@ const something = await Astro.fetchContent('../*.md');
 	}
 	hash := astro.HashFromSource(source)
 
-	transform.ExtractStyles(doc)
+	transform.ExtractStyles(doc, &transform.TransformOptions{})
 	transform.Transform(doc, transform.TransformOptions{
 		Scope: hash,
 	})
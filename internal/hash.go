@@ -9,10 +9,24 @@ import (
 
 // This is used in `Transform` to ensure a stable hash when updating styles
 func HashFromDoc(doc *Node) string {
+	return HashFromSource(docSource(doc))
+}
+
+// Like HashFromDoc, but changes to whitespace alone don't change the hash
+func HashFromDocIgnoringWhitespace(doc *Node) string {
+	return HashFromSourceIgnoringWhitespace(docSource(doc))
+}
+
+func docSource(doc *Node) string {
 	var b strings.Builder
 	PrintToSource(&b, doc)
-	source := strings.TrimSpace(b.String())
-	return HashFromSource(source)
+	return strings.TrimSpace(b.String())
+}
+
+// Like HashFromSource, but every run of whitespace is treated as a single space
+// so reformatting a component keeps the same hash
+func HashFromSourceIgnoringWhitespace(source string) string {
+	return HashFromSource(strings.Join(strings.Fields(source), " "))
 }
 
 func HashFromSource(source string) string {
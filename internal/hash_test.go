@@ -0,0 +1,28 @@
+package astro
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestHashFromSourceIgnoringWhitespace(t *testing.T) {
+	a := "<div>\n  <h1>Hello</h1>\n</div>\n<style>h1 { color: red; }</style>"
+	b := "<div>\n\t\t<h1>Hello</h1>\n</div>\n\n<style>\n\th1 {\n\t\tcolor: red;\n\t}\n</style>\n"
+	if HashFromSourceIgnoringWhitespace(a) != HashFromSourceIgnoringWhitespace(b) {
+		t.Errorf("expected whitespace changes to keep the hash")
+	}
+	if HashFromSource(a) == HashFromSource(b) {
+		t.Errorf("expected HashFromSource to still depend on whitespace")
+	}
+	if HashFromSourceIgnoringWhitespace(a) == HashFromSourceIgnoringWhitespace(strings.Replace(a, "Hello", "Goodbye", 1)) {
+		t.Errorf("expected content changes to change the hash")
+	}
+}
+
+func TestHashFromDocIgnoringWhitespace(t *testing.T) {
+	a, _ := Parse(strings.NewReader("<div>\n  <h1>Hello</h1>\n</div>"))
+	b, _ := Parse(strings.NewReader("<div>\n\t<h1>Hello</h1>\n\n</div>"))
+	if HashFromDocIgnoringWhitespace(a) != HashFromDocIgnoringWhitespace(b) {
+		t.Errorf("expected whitespace changes to keep the hash")
+	}
+}
@@ -156,8 +156,14 @@ func HoistImports(source []byte) HoistedScripts {
 	body := make([]byte, 0)
 	prev := 0
 	for i, statement := NextImportStatement(source, 0); i > -1; i, statement = NextImportStatement(source, i) {
-		body = append(body, source[prev:statement.Span.Start]...)
-		imports = append(imports, statement.Value)
+		// Comments on the lines right above an import, like a license header or
+		// `// @ts-expect-error`, are hoisted along with it
+		startsLine := prev == 0 || source[prev-1] == '\n' || source[prev-1] == '\r'
+		start := prev + leadingCommentsStart(source[prev:statement.Span.Start], startsLine)
+		body = append(body, source[prev:start]...)
+		hoisted := make([]byte, 0, statement.Span.Start-start+len(statement.Value))
+		hoisted = append(hoisted, source[start:statement.Span.Start]...)
+		imports = append(imports, append(hoisted, statement.Value...))
 		prev = i
 	}
 	if prev == 0 {
@@ -167,6 +173,39 @@ func HoistImports(source []byte) HoistedScripts {
 	return HoistedScripts{Hoisted: imports, Body: body}
 }
 
+// Returns the offset of the comments at the end of source which each start on
+// their own line, or len(source) if there are none. A comment following code on
+// the same line belongs to that code instead.
+func leadingCommentsStart(source []byte, startsLine bool) int {
+	l := js.NewLexer(parse.NewInputBytes(source))
+	offset := 0
+	start := -1
+	newline := startsLine
+	for {
+		token, value := l.Next()
+		if token == js.ErrorToken {
+			break
+		}
+		switch {
+		case token == js.LineTerminatorToken:
+			newline = true
+		case token == js.WhitespaceToken:
+		case bytes.HasPrefix(value, []byte("//")) || bytes.HasPrefix(value, []byte("/*")):
+			if start == -1 && newline {
+				start = offset
+			}
+		default:
+			start = -1
+			newline = false
+		}
+		offset += len(value)
+	}
+	if start == -1 {
+		return len(source)
+	}
+	return start
+}
+
 func isIdentifier(value []byte) bool {
 	valid := true
 	for i, b := range value {
@@ -255,6 +294,147 @@ func GetObjectKeys(source []byte) [][]byte {
 	return keys
 }
 
+// Members of the `Astro` global which are only available during on-demand (SSR) rendering
+var ssrAstroMembers = map[string]bool{
+	"cookies":  true,
+	"locals":   true,
+	"redirect": true,
+	"rewrite":  true,
+}
+
+// Find every SSR-only `Astro.*` API used in source, like `Astro.cookies` or `Astro.redirect`.
+// Each API is reported once, in the order it first appears.
+func AstroAPIUsage(source []byte) []string {
+	return findAstroMembers(source, ssrAstroMembers)
+}
+
+// Whether source uses `Astro.fetchContent`, which has been removed in favor of `Astro.glob`
+func UsesFetchContent(source []byte) bool {
+	return len(findAstroMembers(source, map[string]bool{"fetchContent": true})) > 0
+}
+
+func findAstroMembers(source []byte, members map[string]bool) []string {
+	usage := make([]string, 0)
+	if !bytes.Contains(source, []byte("Astro")) {
+		return usage
+	}
+
+	l := js.NewLexer(parse.NewInputBytes(source))
+	i := 0
+	// The last four meaningful tokens, most recent last
+	var tokens [4]js.TokenType
+	var values [4][]byte
+	for {
+		token, value := l.Next()
+
+		if token == js.DivToken || token == js.DivEqToken {
+			lns := bytes.Split(source[i+1:], []byte{'\n'})
+			if bytes.Contains(lns[0], []byte{'/'}) {
+				token, value = l.RegExp()
+			}
+		}
+		i += len(value)
+
+		if token == js.ErrorToken {
+			return usage
+		}
+		if token == js.WhitespaceToken || token == js.LineTerminatorToken || token == js.CommentToken {
+			continue
+		}
+
+		copy(tokens[:], tokens[1:])
+		copy(values[:], values[1:])
+		tokens[3], values[3] = token, value
+
+		// Match `Astro.member` or `Astro?.member`, but not `foo.Astro.member`
+		if token != js.IdentifierToken || !members[string(value)] {
+			continue
+		}
+		if tokens[2] != js.DotToken && tokens[2] != js.OptChainToken {
+			continue
+		}
+		if tokens[1] != js.IdentifierToken || string(values[1]) != "Astro" || tokens[0] == js.DotToken || tokens[0] == js.OptChainToken {
+			continue
+		}
+		api := "Astro." + string(value)
+		found := false
+		for _, existing := range usage {
+			if existing == api {
+				found = true
+				break
+			}
+		}
+		if !found {
+			usage = append(usage, api)
+		}
+	}
+}
+
+type lexedToken struct {
+	tokenType js.TokenType
+	value     []byte
+}
+
+func isName(t lexedToken) bool {
+	return t.tokenType == js.IdentifierToken || isKeyword(t.value)
+}
+
+// Replace member expressions (like `import.meta.env.SSR`) which exactly match
+// a key of define with the matching value. Partial matches like
+// `import.meta.env` or `foo.import.meta.env.SSR` are left alone.
+func ReplaceDefines(source []byte, define map[string]string) []byte {
+	if len(define) == 0 {
+		return source
+	}
+
+	l := js.NewLexer(parse.NewInputBytes(source))
+	tokens := make([]lexedToken, 0)
+	i := 0
+	for {
+		tokenType, value := l.Next()
+
+		if tokenType == js.DivToken || tokenType == js.DivEqToken {
+			lns := bytes.Split(source[i+1:], []byte{'\n'})
+			if bytes.Contains(lns[0], []byte{'/'}) {
+				tokenType, value = l.RegExp()
+			}
+		}
+		i += len(value)
+
+		if tokenType == js.ErrorToken {
+			if l.Err() != io.EOF {
+				return source
+			}
+			break
+		}
+		tokens = append(tokens, lexedToken{tokenType, value})
+	}
+
+	output := make([]byte, 0, len(source))
+	for j := 0; j < len(tokens); j++ {
+		t := tokens[j]
+		if !isName(t) || (j > 0 && (tokens[j-1].tokenType == js.DotToken || tokens[j-1].tokenType == js.OptChainToken)) {
+			output = append(output, t.value...)
+			continue
+		}
+		// Consume the whole `a.b.c` chain
+		chain := append([]byte{}, t.value...)
+		end := j
+		for end+2 < len(tokens) && tokens[end+1].tokenType == js.DotToken && isName(tokens[end+2]) {
+			chain = append(chain, '.')
+			chain = append(chain, tokens[end+2].value...)
+			end += 2
+		}
+		if value, ok := define[string(chain)]; ok {
+			output = append(output, value...)
+		} else {
+			output = append(output, chain...)
+		}
+		j = end
+	}
+	return output
+}
+
 type Import struct {
 	IsType     bool
 	ExportName string
@@ -365,7 +545,8 @@ func NextImportStatement(source []byte, pos int) (int, ImportStatement) {
 					continue
 				}
 
-				if !foundAssertion && next == js.IdentifierToken && string(nextValue) == "assert" {
+				// Import attributes can use either the deprecated `assert` or the `with` keyword
+				if !foundAssertion && foundSpecifier && ((next == js.IdentifierToken && string(nextValue) == "assert") || next == js.WithToken) {
 					foundAssertion = true
 					continue
 				}
@@ -424,3 +605,27 @@ func NextImportStatement(source []byte, pos int) (int, ImportStatement) {
 		i += len(value)
 	}
 }
+
+// Parse the attributes of an import statement, like `{type:'json'}`, into
+// a map of keys to values. Values which are not string literals are skipped.
+func GetImportAttributes(source string) map[string]string {
+	attributes := make(map[string]string)
+	l := js.NewLexer(parse.NewInputBytes([]byte(source)))
+	key := ""
+	for {
+		token, value := l.Next()
+		switch {
+		case token == js.ErrorToken:
+			return attributes
+		case token == js.StringToken && key == "":
+			key = string(value[1 : len(value)-1])
+		case token == js.StringToken:
+			attributes[key] = string(value[1 : len(value)-1])
+			key = ""
+		case token == js.CommaToken:
+			key = ""
+		case key == "" && isIdentifier(value):
+			key = string(value)
+		}
+	}
+}
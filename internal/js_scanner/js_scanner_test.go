@@ -60,14 +60,16 @@ const b = await fetch();`,
 			source: `// comment
 import { fn } from "package";
 const b = await fetch();`,
-			want: `import { fn } from "package";`,
+			want: `// comment
+import { fn } from "package";`,
 		},
 		{
 			name: "import assertion",
 			source: `// comment
 import { fn } from "package" assert { it: 'works' };
 const b = await fetch();`,
-			want: `import { fn } from "package" assert { it: 'works' };`,
+			want: `// comment
+import { fn } from "package" assert { it: 'works' };`,
 		},
 		{
 			name: "import assertion 2",
@@ -79,7 +81,8 @@ import {
     it: 'works'
   };
 const b = await fetch();`,
-			want: `import {
+			want: `// comment
+import {
   fn
 } from
   "package" assert {
@@ -167,10 +170,33 @@ const d = await fetch()
 import { d } from "d";`,
 			want: `import { a } from "a";
 import { b } from "b";
+// comment
 import { c } from "c";
+// comment
 import { d } from "d";
 `,
 		},
+		{
+			name: "license header",
+			source: `/**
+ * @license MIT
+ */
+
+import { a } from "a";
+const b = await fetch();`,
+			want: `/**
+ * @license MIT
+ */
+
+import { a } from "a";
+`,
+		},
+		{
+			name: "trailing comment stays with code",
+			source: `const a = 1; // not about b
+import { b } from "b";`,
+			want: `import { b } from "b";`,
+		},
 		{
 			name:   "assignment",
 			source: `let show = true;`,
@@ -540,3 +566,124 @@ func TestGetObjectKeys(t *testing.T) {
 		})
 	}
 }
+
+func TestAstroAPIUsage(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   []string
+	}{
+		{
+			name:   "none",
+			source: `const { title } = Astro.props;`,
+			want:   []string{},
+		},
+		{
+			name:   "cookies and redirect",
+			source: "const session = Astro.cookies.get('x');\nif (!session) return Astro.redirect('/');",
+			want:   []string{"Astro.cookies", "Astro.redirect"},
+		},
+		{
+			name:   "deduped",
+			source: `Astro.locals.user; Astro.locals.session; Astro?.rewrite('/404')`,
+			want:   []string{"Astro.locals", "Astro.rewrite"},
+		},
+		{
+			name:   "not the global",
+			source: `foo.Astro.cookies; const cookies = "Astro.cookies"; // Astro.redirect`,
+			want:   []string{},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got, _ := json.Marshal(AstroAPIUsage([]byte(tt.source)))
+			want, _ := json.Marshal(tt.want)
+			if diff := test_utils.ANSIDiff(string(want), string(got)); diff != "" {
+				t.Errorf("mismatch (-want +got):\n%s", diff)
+			}
+		})
+	}
+}
+
+func TestReplaceDefines(t *testing.T) {
+	define := map[string]string{
+		"import.meta.env.SSR":        "true",
+		"import.meta.env.PUBLIC_URL": `"https://astro.build"`,
+	}
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "ternary",
+			source: `const url = import.meta.env.SSR ? a : b;`,
+			want:   `const url = true ? a : b;`,
+		},
+		{
+			name:   "string value",
+			source: `fetch(import.meta.env.PUBLIC_URL + '/api')`,
+			want:   `fetch("https://astro.build" + '/api')`,
+		},
+		{
+			name:   "partial match",
+			source: `const env = import.meta.env; const ssr = import.meta.env.SSR_MODE;`,
+			want:   `const env = import.meta.env; const ssr = import.meta.env.SSR_MODE;`,
+		},
+		{
+			name:   "nested member",
+			source: `foo.import.meta.env.SSR`,
+			want:   `foo.import.meta.env.SSR`,
+		},
+		{
+			name:   "strings and comments",
+			source: "// import.meta.env.SSR\nconst a = 'import.meta.env.SSR';",
+			want:   "// import.meta.env.SSR\nconst a = 'import.meta.env.SSR';",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got := string(ReplaceDefines([]byte(tt.source), define))
+			if diff := test_utils.ANSIDiff(tt.want, got); diff != "" {
+				t.Errorf("mismatch (-want +got):\n%s", diff)
+			}
+		})
+	}
+}
+
+func TestGetImportAttributes(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "none",
+			source: "",
+			want:   `{}`,
+		},
+		{
+			name:   "type",
+			source: "{type:'json'}",
+			want:   `{"type":"json"}`,
+		},
+		{
+			name:   "quoted keys",
+			source: `{ "type": "json", 'x-mode': 'raw' }`,
+			want:   `{"type":"json","x-mode":"raw"}`,
+		},
+		{
+			name:   "non-string values",
+			source: "{ type: json, mode: 'raw' }",
+			want:   `{"mode":"raw"}`,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			b, _ := json.Marshal(GetImportAttributes(tt.source))
+			if diff := test_utils.ANSIDiff(tt.want, string(b)); diff != "" {
+				t.Errorf("mismatch (-want +got):\n%s", diff)
+			}
+		})
+	}
+}
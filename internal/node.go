@@ -68,6 +68,13 @@ type HydratedComponentMetadata struct {
 	ResolvedPath string
 }
 
+type MetaTagMetadata struct {
+	// One of "charset", "name", "property" or "http-equiv"
+	TagType string
+	Key     string
+	Content string
+}
+
 // A Node consists of a NodeType and some Data (tag name for element nodes,
 // content for text) and are part of a tree of Nodes. Element nodes may also
 // have a Namespace and contain a slice of Attributes. Data is unescaped, so
@@ -91,7 +98,14 @@ type Node struct {
 	HydratedComponents       []*HydratedComponentMetadata
 	ClientOnlyComponentNodes []*Node
 	ClientOnlyComponents     []*HydratedComponentMetadata
+	ClientOnlyFrameworks     []string
 	HydrationDirectives      map[string]bool
+	CustomElements           []string
+	CSSVariables             []string
+	AstroAPIUsage            []string
+	MetaTags                 []*MetaTagMetadata
+	ContainsHead             bool
+	ResolvedImports          map[string]string
 
 	Type      NodeType
 	DataAtom  atom.Atom
@@ -10,6 +10,7 @@ import (
 
 type PrintCSSResult struct {
 	Output         [][]byte
+	Styles         []*Node // The <style> node that produced each entry of Output
 	SourceMapChunk sourcemap.Chunk
 }
 
@@ -24,16 +25,27 @@ func PrintCSS(sourcetext string, doc *Node, opts transform.TransformOptions) Pri
 	}
 
 	if len(doc.Styles) > 0 {
-		for _, style := range doc.Styles {
-			if style.FirstChild != nil && strings.TrimSpace(style.FirstChild.Data) != "" {
-				p.addSourceMapping(style.Loc[0])
-				p.print(escapeText(strings.TrimSpace(style.FirstChild.Data)))
-				result.Output = append(result.Output, p.output)
-				p.output = []byte{}
-				p.addNilSourceMapping()
-			}
+		for _, style := range printedStyles(doc.Styles) {
+			p.addSourceMapping(style.Loc[0])
+			p.print(escapeText(strings.TrimSpace(style.FirstChild.Data)))
+			result.Output = append(result.Output, p.output)
+			result.Styles = append(result.Styles, style)
+			p.output = []byte{}
+			p.addNilSourceMapping()
 		}
 	}
 
 	return result
 }
+
+// The <style> nodes that PrintCSS emits, in order. Empty styles are skipped,
+// so these line up with the virtual style imports.
+func printedStyles(styles []*Node) []*Node {
+	printed := make([]*Node, 0, len(styles))
+	for _, style := range styles {
+		if style.FirstChild != nil && strings.TrimSpace(style.FirstChild.Data) != "" {
+			printed = append(printed, style)
+		}
+	}
+	return printed
+}
@@ -86,6 +86,9 @@ func printToJs(p *printer, n *Node, cssLen int, opts transform.TransformOptions)
 	return PrintResult{
 		Output:         p.output,
 		SourceMapChunk: p.builder.GenerateChunk(p.output),
+		ComponentName:  getComponentName(opts.Pathname),
+		Modules:        p.modules,
+		TemplateHash:   p.templateHash(),
 	}
 }
 
@@ -100,14 +103,29 @@ func expressionOnlyHasCommentBlock(n *Node) bool {
 		len(removeComments(n.FirstChild.Data)) == 0
 }
 
+func allSameSlot(slots []string) bool {
+	for _, slot := range slots[1:] {
+		if slot != slots[0] {
+			return false
+		}
+	}
+	return true
+}
+
 func render1(p *printer, n *Node, opts RenderOptions) {
 	depth := opts.depth
 
 	// Root of the document, print all children
 	if n.Type == DocumentNode {
+		// Lets the downstream bundler pick the JSX factory for any JSX left in expressions
+		if p.opts.JSXImportSource != "" {
+			p.printf("/* @jsxImportSource %s */\n", p.opts.JSXImportSource)
+		}
 		p.printInternalImports(p.opts.InternalURL)
 		if opts.opts.StaticExtraction {
-			p.printCSSImports(opts.cssLen)
+			p.markAssetStart()
+			p.printCSSImports(n.Styles, opts.cssLen)
+			p.markAssetEnd()
 		}
 
 		for c := n.FirstChild; c != nil; c = c.NextSibling {
@@ -120,6 +138,8 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 			})
 		}
 
+		// Pages with neither a <head> nor any body content still have pending styles
+		p.flushInlineStyles()
 		p.printReturnClose()
 		p.printFuncSuffix(opts.opts)
 		return
@@ -131,7 +151,7 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 			if c.Type == TextNode {
 				p.printInternalImports(p.opts.InternalURL)
 				if opts.opts.StaticExtraction {
-					p.printCSSImports(opts.cssLen)
+					p.printCSSImports(n.Parent.Styles, opts.cssLen)
 				}
 
 				if len(n.Loc) > 0 {
@@ -142,7 +162,7 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 				if len(render.Hoisted) > 0 {
 					for _, hoisted := range render.Hoisted {
 						statement := string(bytes.TrimSpace(hoisted)) + "\n"
-						importStatements += statement
+						importStatements += removeRuntimeFragmentImport(statement, p.opts.InternalURL)
 					}
 				}
 				preprocessed := js_scanner.HoistExports(render.Body)
@@ -173,16 +193,22 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 					if len(definedVars) > 0 {
 						p.printf("const $$definedVars = %s([%s]);\n", DEFINE_STYLE_VARS, strings.Join(definedVars, ","))
 					}
-					p.println("const STYLES = [")
-					for _, style := range n.Parent.Styles {
-						p.printStyleOrScript(opts, style)
+					// Inline styles are printed at the start of the template instead
+					if !opts.opts.EmitCSSInline {
+						p.markAssetStart()
+						p.println("const STYLES = [")
+						for _, style := range n.Parent.Styles {
+							p.printStyleOrScript(opts, style)
+						}
+						p.println("];")
+						p.addNilSourceMapping()
+						p.println(fmt.Sprintf("for (const STYLE of STYLES) %s.styles.add(STYLE);", RESULT))
+						p.markAssetEnd()
 					}
-					p.println("];")
-					p.addNilSourceMapping()
-					p.println(fmt.Sprintf("for (const STYLE of STYLES) %s.styles.add(STYLE);", RESULT))
 				}
 
 				if !opts.opts.StaticExtraction && len(n.Parent.Scripts) > 0 {
+					p.markAssetStart()
 					p.println("const SCRIPTS = [")
 					for _, script := range n.Parent.Scripts {
 						p.printStyleOrScript(opts, script)
@@ -190,9 +216,11 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 					p.println("];")
 					p.addNilSourceMapping()
 					p.println(fmt.Sprintf("for (const SCRIPT of SCRIPTS) %s.scripts.add(SCRIPT);", RESULT))
+					p.markAssetEnd()
 				}
 
 				p.printReturnOpen()
+				p.printInlineStyles(n.Parent)
 			} else {
 				render1(p, c, RenderOptions{
 					isRoot:           false,
@@ -222,15 +250,21 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 			if len(definedVars) > 0 {
 				p.printf("const $$definedVars = %s([%s]);\n", DEFINE_STYLE_VARS, strings.Join(definedVars, ","))
 			}
-			p.println("const STYLES = [")
-			for _, style := range n.Parent.Styles {
-				p.printStyleOrScript(opts, style)
+			// Inline styles are printed at the start of the template instead
+			if !opts.opts.EmitCSSInline {
+				p.markAssetStart()
+				p.println("const STYLES = [")
+				for _, style := range n.Parent.Styles {
+					p.printStyleOrScript(opts, style)
+				}
+				p.println("];")
+				p.addNilSourceMapping()
+				p.println(fmt.Sprintf("for (const STYLE of STYLES) %s.styles.add(STYLE);", RESULT))
+				p.markAssetEnd()
 			}
-			p.println("];")
-			p.addNilSourceMapping()
-			p.println(fmt.Sprintf("for (const STYLE of STYLES) %s.styles.add(STYLE);", RESULT))
 		}
 		if !opts.opts.StaticExtraction && len(n.Parent.Scripts) > 0 {
+			p.markAssetStart()
 			p.println("const SCRIPTS = [")
 			for _, script := range n.Parent.Scripts {
 				p.printStyleOrScript(opts, script)
@@ -238,9 +272,11 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 			p.println("];")
 			p.addNilSourceMapping()
 			p.println(fmt.Sprintf("for (const SCRIPT of SCRIPTS) %s.scripts.add(SCRIPT);", RESULT))
+			p.markAssetEnd()
 		}
 
 		p.printReturnOpen()
+		p.printInlineStyles(n.Parent)
 	}
 	switch n.Type {
 	case TextNode:
@@ -295,6 +331,12 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 
 	// Tip! Comment this block out to debug expressions
 	if n.Expression {
+		// Literals are rendered as static text, escaped the same way the runtime would
+		if value, ok := staticExpressionChild(n); ok {
+			p.addSourceMapping(n.FirstChild.Loc[0])
+			p.print(escapeText(escapeHTML(value)))
+			return
+		}
 		if n.FirstChild == nil {
 			p.print("${(void 0)")
 		} else if expressionOnlyHasCommentBlock(n) {
@@ -357,13 +399,15 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 		// do nothing
 	default:
 		// Before the first non-head element, inject $$maybeRender($$result)
-		// This is for pages that do not contain an explicit head element
+		// This is for pages that do not contain an explicit head element.
+		// Note: <template> is intentionally not listed, it commonly appears in the body
 		switch n.DataAtom {
-		case atom.Html, atom.Head, atom.Base, atom.Basefont, atom.Bgsound, atom.Link, atom.Meta, atom.Noframes, atom.Script, atom.Style, atom.Template, atom.Title:
+		case atom.Html, atom.Head, atom.Base, atom.Basefont, atom.Bgsound, atom.Link, atom.Meta, atom.Noframes, atom.Script, atom.Style, atom.Title:
 			break
 		default:
 			if !*opts.printedMaybeHead {
 				*opts.printedMaybeHead = true
+				p.flushInlineStyles()
 				p.printMaybeRenderHead()
 			}
 		}
@@ -413,10 +457,17 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 		}
 		p.print(`]`)
 	} else {
+		// Attributes are always printed in source order so that later attributes
+		// (including spreads) override earlier ones at runtime. Only attributes
+		// injected by the compiler (scoped `class`, `define:vars` styles) are appended.
 		for _, a := range n.Attr {
 			if transform.IsImplictNodeMarker(a) || a.Key == "is:inline" {
 				continue
 			}
+			if p.opts.StripEventHandlers && isEventHandler(a) {
+				fmt.Printf("%s: removed event handler `%s` from <%s>\n", p.opts.Filename, a.Key, n.Data)
+				continue
+			}
 			if a.Key == "slot" {
 				if n.Parent.Component || n.Parent.Expression {
 					continue
@@ -476,7 +527,8 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 		// }
 	default:
 		isAllWhiteSpace := false
-		if isComponent || isSlot {
+		// A component with `set:slots` still needs its slots printed, even without children
+		if (isComponent && !transform.HasAttr(n, "set:slots")) || isSlot {
 			isAllWhiteSpace = true
 			for c := n.FirstChild; c != nil; c = c.NextSibling {
 				isAllWhiteSpace = c.Type == TextNode && strings.TrimSpace(c.Data) == ""
@@ -507,7 +559,25 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 				p.print(`,`)
 				slottedChildren := make(map[string][]*Node)
 				conditionalSlottedChildren := make([][]*Node, 0)
+				// `set:slots={expr}` forwards an object of slot functions, like the slots this component received
+				forwardedSlots := make([]string, 0)
+				if a := transform.GetAttr(n, "set:slots"); a != nil && a.Type == ExpressionAttribute {
+					forwardedSlots = append(forwardedSlots, strings.TrimSpace(a.Val))
+				}
+				// Comments are slotted along with the content that follows them
+				pendingComments := make([]*Node, 0)
+				lastSlotProp := ""
+				addSlottedChild := func(slotProp string, c *Node) {
+					slottedChildren[slotProp] = append(slottedChildren[slotProp], pendingComments...)
+					slottedChildren[slotProp] = append(slottedChildren[slotProp], c)
+					pendingComments = pendingComments[:0]
+					lastSlotProp = slotProp
+				}
 				for c := n.FirstChild; c != nil; c = c.NextSibling {
+					if c.Type == CommentNode && p.opts.PreserveSlotComments {
+						pendingComments = append(pendingComments, c)
+						continue
+					}
 					slotProp := `"default"`
 					for _, a := range c.Attr {
 						if a.Key == "slot" {
@@ -538,9 +608,15 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 							}
 						}
 
+						// Branches that all target the same slot (like both sides of a ternary)
+						// don't need to be merged, the expression can be the slot's body
+						if len(nestedSlots) > 1 && allSameSlot(nestedSlots) {
+							nestedSlots = nestedSlots[:1]
+						}
+
 						if len(nestedSlots) == 1 {
 							slotProp = nestedSlots[0]
-							slottedChildren[slotProp] = append(slottedChildren[slotProp], c)
+							addSlottedChild(slotProp, c)
 							continue
 						} else if len(nestedSlots) > 1 {
 							conditionalChildren := make([]*Node, 0)
@@ -551,14 +627,14 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 										if a.Type == QuotedAttribute {
 											nestedSlotProp := fmt.Sprintf(`"%s"`, a.Val)
 											nestedSlots = append(nestedSlots, nestedSlotProp)
-											conditionalChildren = append(conditionalChildren, &Node{Type: TextNode, Data: fmt.Sprintf("{%s: () => ", nestedSlotProp), Loc: make([]loc.Loc, 1)})
+											conditionalChildren = append(conditionalChildren, &Node{Type: TextNode, Data: fmt.Sprintf("{%s: %s => ", nestedSlotProp, p.slotParams(nestedSlotProp)), Loc: make([]loc.Loc, 1)})
 											conditionalChildren = append(conditionalChildren, c1)
 											conditionalChildren = append(conditionalChildren, &Node{Type: TextNode, Data: "}", Loc: make([]loc.Loc, 1)})
 											continue child_loop
 										} else if a.Type == ExpressionAttribute {
 											nestedSlotProp := fmt.Sprintf(`[%s]`, a.Val)
 											nestedSlots = append(nestedSlots, nestedSlotProp)
-											conditionalChildren = append(conditionalChildren, &Node{Type: TextNode, Data: fmt.Sprintf("{%s: () => ", nestedSlotProp), Loc: make([]loc.Loc, 1)})
+											conditionalChildren = append(conditionalChildren, &Node{Type: TextNode, Data: fmt.Sprintf("{%s: %s => ", nestedSlotProp, p.slotParams(nestedSlotProp)), Loc: make([]loc.Loc, 1)})
 											conditionalChildren = append(conditionalChildren, c1)
 											conditionalChildren = append(conditionalChildren, &Node{Type: TextNode, Data: "}", Loc: make([]loc.Loc, 1)})
 											continue child_loop
@@ -577,23 +653,32 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 					// Only slot ElementNodes or non-empty TextNodes!
 					// CommentNode and others should not be slotted
 					if c.Type == ElementNode || (c.Type == TextNode && strings.TrimSpace(c.Data) != "") {
-						slottedChildren[slotProp] = append(slottedChildren[slotProp], c)
+						addSlottedChild(slotProp, c)
 					}
 				}
+				// Trailing comments stay with the slot before them, a comment alone
+				// shouldn't create a default slot the author never wrote
+				if len(pendingComments) > 0 && lastSlotProp != "" {
+					slottedChildren[lastSlotProp] = append(slottedChildren[lastSlotProp], pendingComments...)
+				}
 				// fix: sort keys for stable output
 				slottedKeys := make([]string, 0, len(slottedChildren))
 				for k := range slottedChildren {
 					slottedKeys = append(slottedKeys, k)
 				}
 				sort.Strings(slottedKeys)
-				if len(conditionalSlottedChildren) > 0 {
+				if len(conditionalSlottedChildren) > 0 || len(forwardedSlots) > 0 {
 					p.print(`$$mergeSlots(`)
 				}
+				// Forwarded slots come first, so slots passed explicitly take precedence
+				for _, forwarded := range forwardedSlots {
+					p.print(forwarded + ",")
+				}
 				p.print(`{`)
 				if len(slottedKeys) > 0 {
 					for _, slotProp := range slottedKeys {
 						children := slottedChildren[slotProp]
-						p.print(fmt.Sprintf(`%s: () => `, slotProp))
+						p.print(fmt.Sprintf(`%s: %s => `, slotProp, p.slotParams(slotProp)))
 						p.printTemplateLiteralOpen()
 						for _, child := range children {
 							render1(p, child, RenderOptions{
@@ -628,12 +713,19 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 							}
 						}
 					}
+				}
+				if len(conditionalSlottedChildren) > 0 || len(forwardedSlots) > 0 {
 					p.print(`)`)
 				}
 			case isSlot:
 				p.print(`,`)
 				p.printTemplateLiteralOpen()
-				for c := n.FirstChild; c != nil; c = c.NextSibling {
+				// Skip leading whitespace in the fallback content, like component children
+				c := n.FirstChild
+				for c != nil && c.Type == TextNode && strings.TrimSpace(c.Data) == "" {
+					c = c.NextSibling
+				}
+				for ; c != nil; c = c.NextSibling {
 					render1(p, c, RenderOptions{
 						isRoot:           false,
 						isExpression:     opts.isExpression,
@@ -670,7 +762,12 @@ func render1(p *printer, n *Node, opts RenderOptions) {
 	} else if !isImplicit {
 		if n.DataAtom == atom.Head {
 			*opts.printedMaybeHead = true
-			p.printRenderHead()
+			// A duplicate <head> is printed as-is, head content is only rendered once
+			if !p.hasRenderedHead {
+				p.hasRenderedHead = true
+				p.flushInlineStyles()
+				p.printRenderHead()
+			}
 		}
 		p.print(`</` + n.Data + `>`)
 	}
@@ -1,6 +1,7 @@
 package printer
 
 import (
+	"bytes"
 	"fmt"
 	"regexp"
 	"strings"
@@ -16,6 +17,16 @@ import (
 type PrintResult struct {
 	Output         []byte
 	SourceMapChunk sourcemap.Chunk
+	ComponentName  string         // The variable name of the generated component, like `$$Component`
+	Modules        []ModuleImport // Every import statement of the frontmatter, in source order
+	TemplateHash   string         // Hash of Output without style or script content
+}
+
+type ModuleImport struct {
+	Specifier   string
+	Attributes  map[string]string
+	IsCSSImport bool
+	IsTypeOnly  bool
 }
 
 type printer struct {
@@ -26,6 +37,10 @@ type printer struct {
 	hasTypedProps      bool
 	hasInternalImports bool
 	hasCSSImports      bool
+	hasRenderedHead    bool
+	pendingStyles      []*astro.Node
+	assetRanges        [][2]int
+	modules            []ModuleImport
 }
 
 var TEMPLATE_TAG = "$$render"
@@ -61,46 +76,98 @@ func (p *printer) println(text string) {
 	p.output = append(p.output, (text + "\n")...)
 }
 
+// Runtime functions imported from the internal URL, and the names they are
+// referenced by in the generated code
+var internalImports = []string{
+	FRAGMENT,
+	"render as " + TEMPLATE_TAG,
+	"createAstro as " + CREATE_ASTRO,
+	"createComponent as " + CREATE_COMPONENT,
+	"renderComponent as " + RENDER_COMPONENT,
+	"renderHead as " + RENDER_HEAD,
+	"maybeRenderHead as " + MAYBE_RENDER_HEAD,
+	"unescapeHTML as " + UNESCAPE_HTML,
+	"renderSlot as " + RENDER_SLOT,
+	"mergeSlots as " + MERGE_SLOTS,
+	"addAttribute as " + ADD_ATTRIBUTE,
+	"spreadAttributes as " + SPREAD_ATTRIBUTES,
+	"defineStyleVars as " + DEFINE_STYLE_VARS,
+	"defineScriptVars as " + DEFINE_SCRIPT_VARS,
+	"createMetadata as " + CREATE_METADATA,
+}
+
 func (p *printer) printInternalImports(importSpecifier string) {
 	if p.hasInternalImports {
 		return
 	}
+	p.hasInternalImports = true
+	if p.opts.DisableRuntimeImports {
+		fmt.Printf("%s: runtime imports are disabled, these must already be in scope: %s\n", p.opts.Filename, strings.Join(internalImports, ", "))
+		return
+	}
 	p.print("import {\n  ")
-	p.print(FRAGMENT + ",\n  ")
-	p.print("render as " + TEMPLATE_TAG + ",\n  ")
-	p.print("createAstro as " + CREATE_ASTRO + ",\n  ")
-	p.print("createComponent as " + CREATE_COMPONENT + ",\n  ")
-	p.print("renderComponent as " + RENDER_COMPONENT + ",\n  ")
-	p.print("renderHead as " + RENDER_HEAD + ",\n  ")
-	p.print("maybeRenderHead as " + MAYBE_RENDER_HEAD + ",\n  ")
-	p.print("unescapeHTML as " + UNESCAPE_HTML + ",\n  ")
-	p.print("renderSlot as " + RENDER_SLOT + ",\n  ")
-	p.print("mergeSlots as " + MERGE_SLOTS + ",\n  ")
-	p.print("addAttribute as " + ADD_ATTRIBUTE + ",\n  ")
-	p.print("spreadAttributes as " + SPREAD_ATTRIBUTES + ",\n  ")
-	p.print("defineStyleVars as " + DEFINE_STYLE_VARS + ",\n  ")
-	p.print("defineScriptVars as " + DEFINE_SCRIPT_VARS + ",\n  ")
-	p.print("createMetadata as " + CREATE_METADATA)
+	p.print(strings.Join(internalImports, ",\n  "))
 	p.print("\n} from \"")
-	p.print(importSpecifier)
+	p.print(escapeDoubleQuote(importSpecifier))
 	p.print("\";\n")
-	p.hasInternalImports = true
 }
 
-func (p *printer) printCSSImports(cssLen int) {
-	if p.hasCSSImports {
+func (p *printer) printCSSImports(styles []*astro.Node, cssLen int) {
+	if p.hasCSSImports || p.opts.DisableRuntimeImports || p.opts.EmitCSSInline {
 		return
 	}
+	filename := p.opts.NormalizedFilename
+	if filename == "" {
+		filename = p.opts.Filename
+	}
+	printed := printedStyles(styles)
 	i := 0
 	for i < cssLen {
+		// An explicit `OutputExtension` wins over the `lang` of each <style>
+		ext := p.opts.OutputExtension
+		if ext == "" && i < len(printed) {
+			if lang := transform.GetAttr(printed[i], "lang"); lang != nil && lang.Type == astro.QuotedAttribute {
+				ext = lang.Val
+			}
+		}
+		if ext == "" {
+			ext = "css"
+		}
 		// import '/src/pages/index.astro?astro&type=style&index=0&lang.css';
-		p.print(fmt.Sprintf("import \"%s?astro&type=style&index=%v&lang.css\";", p.opts.Filename, i))
+		p.print(fmt.Sprintf("import \"%s?astro&type=style&index=%v&lang.%s\";", escapeDoubleQuote(filename), i, escapeDoubleQuote(ext)))
 		i++
 	}
 	p.print("\n")
 	p.hasCSSImports = true
 }
 
+// Everything printed between markAssetStart and markAssetEnd is style or
+// script content, which templateHash leaves out
+func (p *printer) markAssetStart() {
+	p.assetRanges = append(p.assetRanges, [2]int{len(p.output), len(p.output)})
+}
+
+func (p *printer) markAssetEnd() {
+	p.assetRanges[len(p.assetRanges)-1][1] = len(p.output)
+}
+
+// Hash of the output without any style or script content, so it only changes
+// when the template does. The scope is left out too, since it's derived from
+// the whole source and already returned on its own.
+func (p *printer) templateHash() string {
+	template := make([]byte, 0, len(p.output))
+	i := 0
+	for _, r := range p.assetRanges {
+		template = append(template, p.output[i:r[0]]...)
+		i = r[1]
+	}
+	template = append(template, p.output[i:]...)
+	if p.opts.Scope != "" {
+		template = bytes.ReplaceAll(template, []byte(p.opts.Scope), nil)
+	}
+	return astro.HashFromSource(string(template))
+}
+
 func (p *printer) printRenderHead() {
 	p.addNilSourceMapping()
 	p.print(fmt.Sprintf("${%s(%s)}", RENDER_HEAD, RESULT))
@@ -117,6 +184,66 @@ func (p *printer) printReturnOpen() {
 	p.printTemplateLiteralOpen()
 }
 
+// With `EmitCSSInline`, every style is printed as a <style> at the start of
+// the template, so the component doesn't need any virtual style imports.
+// Anything before <!DOCTYPE> or <html> puts browsers in quirks mode, so pages
+// print them in <head> instead, see flushInlineStyles.
+func (p *printer) printInlineStyles(doc *astro.Node) {
+	if !p.opts.EmitCSSInline {
+		return
+	}
+	if isPage(doc) {
+		p.pendingStyles = doc.Styles
+		return
+	}
+	p.printStyleTags(doc.Styles)
+}
+
+// Prints the styles deferred by printInlineStyles, at most once
+func (p *printer) flushInlineStyles() {
+	styles := p.pendingStyles
+	p.pendingStyles = nil
+	p.printStyleTags(styles)
+}
+
+// Whether the component has an explicit <html> or <head>
+func isPage(doc *astro.Node) bool {
+	if doc.ContainsHead {
+		return true
+	}
+	for c := doc.FirstChild; c != nil; c = c.NextSibling {
+		if c.Type == astro.ElementNode && c.DataAtom == atom.Html && !transform.IsImplictNode(c) {
+			return true
+		}
+	}
+	return false
+}
+
+func (p *printer) printStyleTags(styles []*astro.Node) {
+	// doc.Styles is collected in reverse, print in authored order so the cascade is preserved
+	printed := printedStyles(styles)
+	p.markAssetStart()
+	for i := len(printed) - 1; i >= 0; i-- {
+		style := printed[i]
+		p.addSourceMapping(style.Loc[0])
+		p.print("<style>")
+		p.print(escapeText(strings.TrimSpace(style.FirstChild.Data)))
+		p.print("</style>")
+	}
+	p.markAssetEnd()
+}
+
+// The parameters of a component slot function. Slots named in ScopedSlotNames
+// receive the render result, slots with a dynamic name never do.
+func (p *printer) slotParams(slotProp string) string {
+	for _, name := range p.opts.ScopedSlotNames {
+		if slotProp == fmt.Sprintf(`"%s"`, name) {
+			return "($$result)"
+		}
+	}
+	return "()"
+}
+
 func (p *printer) printReturnClose() {
 	p.addNilSourceMapping()
 	p.printTemplateLiteralClose()
@@ -198,26 +325,41 @@ func (p *printer) printFuncSuffix(opts transform.TransformOptions) {
 	p.addNilSourceMapping()
 	p.println("});")
 	p.println(fmt.Sprintf("export default %s;", componentName))
+	if opts.HMR {
+		p.printHMR(componentName)
+	}
+}
+
+// Accept hot updates in development. This must come after the default export
+// so the component binding is in scope.
+func (p *printer) printHMR(componentName string) {
+	p.println("if (import.meta.hot) {")
+	p.println(fmt.Sprintf("  import.meta.hot.data.%s = %s;", componentName, componentName))
+	p.println("  import.meta.hot.accept();")
+	p.println("}")
 }
 
 func (p *printer) printAttributesToObject(n *astro.Node) {
-	lastAttributeSkipped := false
+	printedAttribute := false
 	p.print("{")
-	for i, a := range n.Attr {
-		if i != 0 && !lastAttributeSkipped {
-			p.print(",")
+	for _, a := range n.Attr {
+		if a.Key == "set:text" || a.Key == "set:html" || a.Key == "set:slots" || a.Key == "is:raw" {
+			continue
 		}
-		if a.Key == "set:text" || a.Key == "set:html" || a.Key == "is:raw" {
+		if a.Type == astro.ShorthandAttribute && len(removeComments(a.Key)) == 0 {
 			continue
 		}
-		lastAttributeSkipped = false
+		if printedAttribute {
+			p.print(",")
+		}
+		printedAttribute = true
 		switch a.Type {
 		case astro.QuotedAttribute:
 			p.addSourceMapping(a.KeyLoc)
 			p.print(`"` + a.Key + `"`)
 			p.print(":")
 			p.addSourceMapping(a.ValLoc)
-			p.print(`"` + a.Val + `"`)
+			p.print(`"` + escapeDoubleQuote(a.Val) + `"`)
 		case astro.EmptyAttribute:
 			p.addSourceMapping(a.KeyLoc)
 			p.print(`"` + a.Key + `"`)
@@ -238,10 +380,6 @@ func (p *printer) printAttributesToObject(n *astro.Node) {
 			p.print(`...(` + strings.TrimSpace(a.Key) + `)`)
 		case astro.ShorthandAttribute:
 			withoutComments := removeComments(a.Key)
-			if len(withoutComments) == 0 {
-				lastAttributeSkipped = true
-				continue
-			}
 			p.addSourceMapping(a.KeyLoc)
 			p.print(`"` + withoutComments + `"`)
 			p.print(":")
@@ -290,17 +428,19 @@ func (p *printer) printStyleOrScript(opts RenderOptions, n *astro.Node) {
 }
 
 func (p *printer) printAttribute(attr astro.Attribute, n *astro.Node) {
-	if attr.Key == "define:vars" || attr.Key == "set:text" || attr.Key == "set:html" || attr.Key == "is:raw" {
+	if attr.Key == "define:vars" || attr.Key == "set:text" || attr.Key == "set:html" || attr.Key == "set:slots" || attr.Key == "is:raw" {
 		return
 	}
 
-	if attr.Namespace != "" || attr.Type == astro.QuotedAttribute || attr.Type == astro.EmptyAttribute {
-		p.print(" ")
+	// Namespaced XML attributes (`xlink:href`, `xml:space`) are split by the parser.
+	// Join them back together so every attribute type emits the full name.
+	if attr.Namespace != "" {
+		attr.Key = fmt.Sprintf("%s:%s", attr.Namespace, attr.Key)
+		attr.Namespace = ""
 	}
 
-	if attr.Namespace != "" {
-		p.print(attr.Namespace)
-		p.print(":")
+	if attr.Type == astro.QuotedAttribute || attr.Type == astro.EmptyAttribute {
+		p.print(" ")
 	}
 
 	switch attr.Type {
@@ -309,11 +449,25 @@ func (p *printer) printAttribute(attr astro.Attribute, n *astro.Node) {
 		p.print(attr.Key)
 		p.print("=")
 		p.addSourceMapping(attr.ValLoc)
-		p.print(`"` + encodeDoubleQuote(escapeInterpolation(escapeBackticks(attr.Val))) + `"`)
+		p.print(`"`)
+		p.print(encodeDoubleQuote(escapeText(attr.Val)))
+		p.print(`"`)
 	case astro.EmptyAttribute:
 		p.addSourceMapping(attr.KeyLoc)
 		p.print(attr.Key)
 	case astro.ExpressionAttribute:
+		// Non-empty literals are printed like quoted attributes, escaped the same way the runtime would
+		if value, ok := staticExpressionValue(attr.Val); ok && value != "" && attr.Key != "class:list" {
+			p.print(" ")
+			p.addSourceMapping(attr.KeyLoc)
+			p.print(strings.TrimSpace(attr.Key))
+			p.print("=")
+			p.addSourceMapping(attr.ValLoc)
+			p.print(`"`)
+			p.print(escapeText(escapeHTML(value)))
+			p.print(`"`)
+			return
+		}
 		p.print(fmt.Sprintf("${%s(", ADD_ATTRIBUTE))
 		p.addSourceMapping(attr.ValLoc)
 		if strings.TrimSpace(attr.Val) == "" {
@@ -395,6 +549,24 @@ func remove(slice []*astro.Node, node *astro.Node) []*astro.Node {
 	return append(slice[:s], slice[s+1:]...)
 }
 
+// Whether an import statement has bindings other than client:only components.
+// A namespace import counts as client:only when one of its members is used that way.
+func hasNonClientOnlyImports(statement js_scanner.ImportStatement, nodes []*astro.Node) bool {
+outer:
+	for _, imported := range statement.Imports {
+		if imported.IsType {
+			continue
+		}
+		for _, n := range nodes {
+			if n.Data == imported.LocalName || (imported.ExportName == "*" && strings.HasPrefix(n.Data, imported.LocalName+".")) {
+				continue outer
+			}
+		}
+		return true
+	}
+	return false
+}
+
 func (p *printer) printComponentMetadata(doc *astro.Node, opts transform.TransformOptions, source []byte) {
 	var specs []string
 	var asrts []string
@@ -405,6 +577,14 @@ func (p *printer) printComponentMetadata(doc *astro.Node, opts transform.Transfo
 	modCount := 1
 	loc, statement := js_scanner.NextImportStatement(source, 0)
 	for loc != -1 {
+		isCSSImport := len(statement.Imports) == 0 && styleModuleSpecExp.MatchString(statement.Specifier)
+		p.modules = append(p.modules, ModuleImport{
+			Specifier:   statement.Specifier,
+			Attributes:  js_scanner.GetImportAttributes(statement.Assertions),
+			IsCSSImport: isCSSImport,
+			IsTypeOnly:  statement.IsType,
+		})
+
 		isClientOnlyImport := false
 	component_loop:
 		for _, n := range doc.ClientOnlyComponentNodes {
@@ -461,18 +641,15 @@ func (p *printer) printComponentMetadata(doc *astro.Node, opts transform.Transfo
 				continue component_loop
 			}
 		}
-		if !isClientOnlyImport {
+		// Statements that also import other bindings are kept in the module metadata,
+		// since those bindings are imported on the server anyway
+		if !isClientOnlyImport || hasNonClientOnlyImports(statement, doc.ClientOnlyComponentNodes) {
 			assertions := ""
 			if statement.Assertions != "" {
-				assertions += " assert "
+				assertions += " with "
 				assertions += statement.Assertions
 			}
 
-			isCSSImport := false
-			if len(statement.Imports) == 0 && styleModuleSpecExp.MatchString(statement.Specifier) {
-				isCSSImport = true
-			}
-
 			if !isCSSImport && !statement.IsType {
 				p.print(fmt.Sprintf("\nimport * as $$module%v from '%s'%s;", modCount, statement.Specifier, assertions))
 				specs = append(specs, statement.Specifier)
@@ -562,6 +739,7 @@ conly_loop:
 	}
 	// Hoisted scripts
 	p.print("]), hoisted: [")
+	p.markAssetStart()
 	for i, node := range doc.Scripts {
 		if i > 0 {
 			p.print(", ")
@@ -580,13 +758,16 @@ conly_loop:
 					params = append(params, ',')
 				}
 			}
-			p.print(fmt.Sprintf("{ type: 'define:vars', value: `%s`, keys: '%s' }", escapeInterpolation(escapeBackticks(node.FirstChild.Data)), escapeSingleQuote(string(params))))
+			p.print(fmt.Sprintf("{ type: 'define:vars', value: `%s`, keys: '%s' }", escapeText(node.FirstChild.Data), escapeSingleQuote(string(params))))
 		case src != nil:
 			p.print(fmt.Sprintf("{ type: 'external', src: '%s' }", escapeSingleQuote(src.Val)))
 		case node.FirstChild != nil:
-			p.print(fmt.Sprintf("{ type: 'inline', value: `%s` }", escapeInterpolation(escapeBackticks(node.FirstChild.Data))))
+			p.print(fmt.Sprintf("{ type: 'inline', value: `%s` }", escapeText(node.FirstChild.Data)))
 		}
 	}
 
-	p.print("] });\n\n")
+	p.markAssetEnd()
+
+	// Scope hash, so the runtime can look up the CSS scope without rehashing
+	p.print(fmt.Sprintf("], scope: '%s' });\n\n", opts.Scope))
 }
@@ -52,7 +52,7 @@ func TestPrinterCSS(t *testing.T) {
 			}
 
 			hash := astro.HashFromSource(code)
-			transform.ExtractStyles(doc)
+			transform.ExtractStyles(doc, &transform.TransformOptions{})
 			transform.Transform(doc, transform.TransformOptions{Scope: hash}) // note: we want to test Transform in context here, but more advanced cases could be tested separately
 			result := PrintCSS(code, doc, transform.TransformOptions{
 				Scope:       "astro-XXXX",
@@ -79,3 +79,67 @@ func TestPrinterCSS(t *testing.T) {
 		})
 	}
 }
+
+// Style preprocessing and `PrintCSS` both rely on `doc.Styles`, so every
+// extracted style must come out of `PrintCSS` in the same order.
+func TestPrintCSSMatchesExtractedStyles(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		count  int
+	}{
+		{
+			name:   "top-level",
+			source: `<style>.a{}</style><style>.b{}</style><div />`,
+			count:  2,
+		},
+		{
+			name:   "empty style",
+			source: `<style></style><style>.a{}</style><div />`,
+			count:  1,
+		},
+		{
+			name:   "ignored styles",
+			source: `<style is:inline>.a{}</style><svg><style>.b{}</style></svg><noscript><style>.c{}</style></noscript><style>.d{}</style>`,
+			count:  1,
+		},
+		{
+			name:   "expression branches",
+			source: `<style>.a{}</style>{cond ? <div><style>.b{}</style></div> : <div><style>.c{}</style></div>}{cond && <style>.d{}</style>}`,
+			count:  3,
+		},
+		{
+			name:   "component children",
+			source: `<Component><style>.a{}</style></Component><style>.b{}</style>`,
+			count:  2,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			transform.ExtractStyles(doc, &transform.TransformOptions{})
+			transform.Transform(doc, transform.TransformOptions{Scope: "XXXX"})
+			result := PrintCSS(tt.source, doc, transform.TransformOptions{})
+
+			if len(result.Output) != tt.count || len(result.Styles) != tt.count {
+				t.Fatalf("\nFAIL: %s\n  want %d styles, got %d", tt.name, tt.count, len(result.Output))
+			}
+			i := 0
+			for _, style := range doc.Styles {
+				if style.FirstChild == nil || strings.TrimSpace(style.FirstChild.Data) == "" {
+					continue
+				}
+				if result.Styles[i] != style {
+					t.Errorf("\nFAIL: %s\n  style %d is out of order", tt.name, i)
+				}
+				if got, want := string(result.Output[i]), strings.TrimSpace(style.FirstChild.Data); got != want {
+					t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, want, got)
+				}
+				i++
+			}
+		})
+	}
+}
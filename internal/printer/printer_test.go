@@ -10,6 +10,7 @@ import (
 
 	astro "github.com/withastro/compiler/internal"
 	types "github.com/withastro/compiler/internal/t"
+	"github.com/withastro/compiler/internal/sourcemap"
 	"github.com/withastro/compiler/internal/test_utils"
 	"github.com/withastro/compiler/internal/transform"
 )
@@ -144,6 +145,20 @@ func TestPrinter(t *testing.T) {
 				code: "${$$renderComponent($$result,'Component',Component,{},{\"test\": () => $$render`${value && $$render`${$$maybeRenderHead($$result)}<div>foo</div>`}`,})}",
 			},
 		},
+		{
+			name:   "forwarded slots",
+			source: `<Component {...Astro.props} set:slots={$$slots} />`,
+			want: want{
+				code: "${$$renderComponent($$result,'Component',Component,{...(Astro.props)},$$mergeSlots($$slots,{}))}",
+			},
+		},
+		{
+			name:   "forwarded slots with explicit slots",
+			source: `<Component {...Astro.props} set:slots={$$slots}><div>Default</div><div slot="a">A</div></Component>`,
+			want: want{
+				code: "${$$renderComponent($$result,'Component',Component,{...(Astro.props)},$$mergeSlots($$slots,{\"a\": () => $$render`${$$maybeRenderHead($$result)}<div>A</div>`,\"default\": () => $$render`<div>Default</div>`,}))}",
+			},
+		},
 		{
 			name:   "ternary slot",
 			source: `<Component>{Math.random() > 0.5 ? <div slot="a">A</div> : <div slot="b">B</div>}</Component>`,
@@ -151,6 +166,20 @@ func TestPrinter(t *testing.T) {
 				code: "${$$renderComponent($$result,'Component',Component,{},$$mergeSlots({},Math.random() > 0.5 ? {\"a\": () => $$render`${$$maybeRenderHead($$result)}<div>A</div>`} : {\"b\": () => $$render`<div>B</div>`}))}",
 			},
 		},
+		{
+			name:   "ternary same slot",
+			source: `<Component>{cond ? <div slot="x">A</div> : <div slot="x">B</div>}</Component>`,
+			want: want{
+				code: "${$$renderComponent($$result,'Component',Component,{},{\"x\": () => $$render`${cond ? $$render`${$$maybeRenderHead($$result)}<div>A</div>` : $$render`<div>B</div>`}`,})}",
+			},
+		},
+		{
+			name:   "ternary same slot with other slots",
+			source: `<Component><div>Default</div>{cond ? <div slot="x">A</div> : <div slot="x">B</div>}</Component>`,
+			want: want{
+				code: "${$$renderComponent($$result,'Component',Component,{},{\"default\": () => $$render`${$$maybeRenderHead($$result)}<div>Default</div>`,\"x\": () => $$render`${cond ? $$render`<div>A</div>` : $$render`<div>B</div>`}`,})}",
+			},
+		},
 		{
 			name:   "function expression slots",
 			source: "<Component>\n{() => { switch (value) {\ncase 'a': return <div slot=\"a\">A</div>\ncase 'b': return <div slot=\"b\">B</div>\ncase 'c': return <div slot=\"c\">C</div>\n}\n}}\n</Component>",
@@ -200,6 +229,144 @@ func TestPrinter(t *testing.T) {
 				code: `${$$maybeRenderHead($$result)}<div>${$$renderSlot($$result,$$slots["test"],$$render` + BACKTICK + `<p>Fallback</p>` + BACKTICK + `)}</div>`,
 			},
 		},
+		{
+			name:   "Astro global in an attribute",
+			source: `<a href={Astro.url.pathname}>Home</a>`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<a${` + ADD_ATTRIBUTE + `(Astro.url.pathname, "href")}>Home</a>`,
+			},
+		},
+		{
+			name:   "Astro global destructured in an expression",
+			source: `<h1>{(({ title }) => title)(Astro.props)}</h1>`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<h1>${(({ title }) => title)(Astro.props)}</h1>`,
+			},
+		},
+		{
+			name:   "Astro global in a conditional",
+			source: `{Astro.slots.has("title") && <h1><slot name="title" /></h1>}`,
+			want: want{
+				code: "${Astro.slots.has(\"title\") && $$render`${$$maybeRenderHead($$result)}<h1>${$$renderSlot($$result,$$slots[\"title\"])}</h1>`}",
+			},
+		},
+		{
+			name:   "meta charset before body content",
+			source: `<meta charset="utf-8"><div>x</div>`,
+			want: want{
+				code: `<meta charset="utf-8">${$$maybeRenderHead($$result)}<div>x</div>`,
+			},
+		},
+		{
+			name:   "head elements before body content",
+			source: `<title>T</title><base href="/"><link rel="icon" href="/favicon.svg"><style is:inline>a{}</style><div>x</div>`,
+			want: want{
+				code: `<title>T</title><base href="/"><link rel="icon" href="/favicon.svg"><style>a{}</style>${$$maybeRenderHead($$result)}<div>x</div>`,
+			},
+		},
+		{
+			name:   "top-level link with dynamic href",
+			source: `<link rel="stylesheet" href={url}><div />`,
+			want: want{
+				code: `<link rel="stylesheet"${` + ADD_ATTRIBUTE + `(url, "href")}>${$$maybeRenderHead($$result)}<div></div>`,
+			},
+		},
+		{
+			name:   "slot with fallback leading whitespace",
+			source: "<div><slot name=\"test\">\n  <p>Fallback</p>\n</slot></div>",
+			want: want{
+				code: "${$$maybeRenderHead($$result)}<div>${$$renderSlot($$result,$$slots[\"test\"],$$render`<p>Fallback</p>\n`)}</div>",
+			},
+		},
+		{
+			name:   "slot with fallback expression",
+			source: `<slot name="title">{Astro.props.defaultTitle}</slot>`,
+			want: want{
+				code: `${$$renderSlot($$result,$$slots["title"],$$render` + BACKTICK + `${Astro.props.defaultTitle}` + BACKTICK + `)}`,
+			},
+		},
+		{
+			name: "slot with fallback expression referencing props",
+			source: `---
+const { defaultTitle } = Astro.props;
+---
+<h1><slot name="title">{defaultTitle}</slot></h1>`,
+			want: want{
+				frontmatter: []string{"", "const { defaultTitle } = Astro.props;"},
+				code:        `${$$maybeRenderHead($$result)}<h1>${$$renderSlot($$result,$$slots["title"],$$render` + BACKTICK + `${defaultTitle}` + BACKTICK + `)}</h1>`,
+			},
+		},
+		{
+			name:   "svg namespaced attributes",
+			source: `<svg xml:space="preserve"><use xlink:href="#icon" /></svg>`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<svg xml:space="preserve"><use xlink:href="#icon"></use></svg>`,
+			},
+		},
+		{
+			name:   "svg namespaced expression attribute",
+			source: `<svg><use xlink:href={href} /></svg>`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<svg><use${$$addAttribute(href, "xlink:href")}></use></svg>`,
+			},
+		},
+		{
+			name:   "attributes preserve source order",
+			source: `<div b="1" a={x} transition:name="fade" c></div>`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<div b="1"${$$addAttribute(x, "a")} transition:name="fade" c></div>`,
+			},
+		},
+		{
+			name:   "component attributes preserve source order",
+			source: `<Component b="1" transition:name="fade" a={x} />`,
+			want: want{
+				code: `${$$renderComponent($$result,'Component',Component,{"b":"1","transition:name":"fade","a":(x)})}`,
+			},
+		},
+		{
+			name:   "template with style is not hoisted",
+			source: `<template id="my-tmpl"><style>.foo { color: red }</style></template>`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<template id="my-tmpl"><style>.foo { color: red }</style></template>`,
+			},
+		},
+		{
+			name:   "template after body element",
+			source: `<div></div><template><p>x</p></template>`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<div></div><template><p>x</p></template>`,
+			},
+		},
+		{
+			name:   "hoisted script inside component slot",
+			source: `<Card><script>console.log("a")</script><p>A</p></Card>`,
+			want: want{
+				scripts: []string{"{props:{},children:`console.log(\"a\")`}"},
+				code:    "${$$renderComponent($$result,'Card',Card,{},{\"default\": () => $$render`${$$maybeRenderHead($$result)}<p>A</p>`,})}",
+			},
+		},
+		{
+			name:   "inline script inside component slot",
+			source: `<Card><div><script is:inline>console.log("a")</script></div></Card>`,
+			want: want{
+				code: "${$$renderComponent($$result,'Card',Card,{},{\"default\": () => $$render`${$$maybeRenderHead($$result)}<div><script>console.log(\"a\")</script></div>`,})}",
+			},
+		},
+		{
+			name:   "spread between static attributes keeps source order",
+			source: `<div class="a" {...props} id="b"></div>`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<div class="a"${$$spreadAttributes(props,"props")} id="b"></div>`,
+			},
+		},
+		{
+			name:   "spread between expression attributes keeps source order",
+			source: `<div title={a} {...props} data-id={b}></div>`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<div${$$addAttribute(a, "title")}${$$spreadAttributes(props,"props")}${$$addAttribute(b, "data-id")}></div>`,
+			},
+		},
 		{
 			name:   "text only",
 			source: "Hello!",
@@ -232,6 +399,52 @@ const href = '/about';
 				code:        `${$$maybeRenderHead($$result)}<a${` + ADD_ATTRIBUTE + `(href, "href")}>About</a>`,
 			},
 		},
+		{
+			name: "typescript is preserved",
+			source: `---
+interface Link { href: string }
+const link = { href: '/about' } satisfies Link;
+---
+<a href={link.href as string}>About</a>`,
+			want: want{
+				frontmatter: []string{"", `interface Link { href: string }
+const link = { href: '/about' } satisfies Link;`},
+				code: `${$$maybeRenderHead($$result)}<a${` + ADD_ATTRIBUTE + `(link.href as string, "href")}>About</a>`,
+			},
+		},
+		{
+			name: "local component variable",
+			source: `---
+const Tag = Astro.props.href ? Link : Button;
+---
+<Tag class="action">Go</Tag>`,
+			want: want{
+				frontmatter: []string{"", `const Tag = Astro.props.href ? Link : Button;`},
+				code:        "${$$renderComponent($$result,'Tag',Tag,{\"class\":\"action\"},{\"default\": () => $$render`Go`,})}",
+			},
+		},
+		{
+			name: "non-terminating frontmatter",
+			source: `---
+while (true) {}
+---
+<div />`,
+			want: want{
+				frontmatter: []string{"", `while (true) {}`},
+				code:        `${$$maybeRenderHead($$result)}<div></div>`,
+			},
+		},
+		{
+			name: "type-only Astro reference",
+			source: `---
+const x: typeof Astro.props = {};
+---
+<div />`,
+			want: want{
+				frontmatter: []string{"", `const x: typeof Astro.props = {};`},
+				code:        `${$$maybeRenderHead($$result)}<div></div>`,
+			},
+		},
 		{
 			name: "getStaticPaths (basic)",
 			source: `---
@@ -307,6 +520,19 @@ import data from "test" assert { type: 'json' };
 				metadata: metadata{modules: []string{`{ module: $$module1, specifier: 'test', assert: {type:'json'} }`}},
 			},
 		},
+		{
+			name: "import attributes",
+			source: `---
+import data from './x.json' with { type: 'json' };
+---
+`,
+			want: want{
+				frontmatter: []string{
+					`import data from './x.json' with { type: 'json' };`,
+				},
+				metadata: metadata{modules: []string{`{ module: $$module1, specifier: './x.json', assert: {type:'json'} }`}},
+			},
+		},
 		{
 			name:   "no expressions in math",
 			source: `<p>Hello, world! This is a <em>buggy</em> formula: <span class="math math-inline"><span class="katex"><span class="katex-mathml"><math xmlns="http://www.w3.org/1998/Math/MathML"><semantics><mrow><mi>f</mi><mspace></mspace><mspace width="0.1111em"></mspace><mo lspace="0em" rspace="0.17em"></mo><mtext> ⁣</mtext><mo lspace="0em" rspace="0em">:</mo><mspace width="0.3333em"></mspace><mi>X</mi><mo>→</mo><msup><mi mathvariant="double-struck">R</mi><mrow><mn>2</mn><mi>x</mi></mrow></msup></mrow><annotation encoding="application/x-tex">f\colon X \to \mathbb R^{2x}</annotation></semantics></math></span><span class="katex-html" aria-hidden="true"><span class="base"><span class="strut" style="height:0.8889em;vertical-align:-0.1944em;"></span><span class="mord mathnormal" style="margin-right:0.10764em;">f</span><span class="mspace nobreak"></span><span class="mspace" style="margin-right:0.1111em;"></span><span class="mpunct"></span><span class="mspace" style="margin-right:-0.1667em;"></span><span class="mspace" style="margin-right:0.1667em;"></span><span class="mord"><span class="mrel">:</span></span><span class="mspace" style="margin-right:0.3333em;"></span><span class="mord mathnormal" style="margin-right:0.07847em;">X</span><span class="mspace" style="margin-right:0.2778em;"></span><span class="mrel">→</span><span class="mspace" style="margin-right:0.2778em;"></span></span><span class="base"><span class="strut" style="height:0.8141em;"></span><span class="mord"><span class="mord mathbb">R</span><span class="msupsub"><span class="vlist-t"><span class="vlist-r"><span class="vlist" style="height:0.8141em;"><span style="top:-3.063em;margin-right:0.05em;"><span class="pstrut" style="height:2.7em;"></span><span class="sizing reset-size6 size3 mtight"><span class="mord mtight"><span class="mord mtight">2</span><span class="mord mathnormal mtight">x</span></span></span></span></span></span></span></span></span></span></span></span></span></p>`,
@@ -326,13 +552,33 @@ import data from "test";
 `,
 			want: want{
 				frontmatter: []string{
-					`import data from "test";`,
-					"let testWord = \"Test\"\n// comment",
+					"// comment\nimport data from \"test\";",
+					"let testWord = \"Test\"",
 				},
 				metadata: metadata{modules: []string{`{ module: $$module1, specifier: 'test', assert: {} }`}},
 				code:     "${$$maybeRenderHead($$result)}<div>${data}</div>",
 			},
 		},
+		{
+			name: "license header",
+			source: `---
+/* @license MIT */
+// @ts-expect-error
+import data from "test";
+const value = data;
+---
+
+<div>{value}</div>
+`,
+			want: want{
+				frontmatter: []string{
+					"/* @license MIT */\n// @ts-expect-error\nimport data from \"test\";",
+					"const value = data;",
+				},
+				metadata: metadata{modules: []string{`{ module: $$module1, specifier: 'test', assert: {} }`}},
+				code:     "${$$maybeRenderHead($$result)}<div>${value}</div>",
+			},
+		},
 		{
 			name: "type import",
 			source: `---
@@ -568,6 +814,39 @@ import * as components from '../components';
   </body></html>`,
 			},
 		},
+		{
+			name: "client:only component (default and named)",
+			source: `---
+import Component, { helper } from '../components';
+const value = helper();
+---
+<Component client:only />`,
+			want: want{
+				frontmatter: []string{"import Component, { helper } from '../components';", "const value = helper();"},
+				metadata: metadata{
+					modules:              []string{`{ module: $$module1, specifier: '../components', assert: {} }`},
+					hydrationDirectives:  []string{"only"},
+					clientOnlyComponents: []string{"../components"},
+				},
+				code: `${` + RENDER_COMPONENT + `($$result,'Component',null,{"client:only":true,"client:component-hydration":"only","client:component-path":($$metadata.resolvePath("../components")),"client:component-export":"default"})}`,
+			},
+		},
+		{
+			name: "client:only component (namespace used as a value)",
+			source: `---
+import * as components from '../components';
+const value = components.helper();
+---
+<components.A client:only />`,
+			want: want{
+				frontmatter: []string{"import * as components from '../components';", "const value = components.helper();"},
+				metadata: metadata{
+					hydrationDirectives:  []string{"only"},
+					clientOnlyComponents: []string{"../components"},
+				},
+				code: `${` + RENDER_COMPONENT + `($$result,'components.A',null,{"client:only":true,"client:component-hydration":"only","client:component-path":($$metadata.resolvePath("../components")),"client:component-export":"A"})}`,
+			},
+		},
 		{
 			name: "client:only component (multiple)",
 			source: `---
@@ -991,9 +1270,9 @@ const someProps = {
   </body>
 </html>`,
 			want: want{
-				frontmatter: []string{`import Counter from '../components/Counter.jsx'`,
-					`// Component Imports
-const someProps = {
+				frontmatter: []string{`// Component Imports
+import Counter from '../components/Counter.jsx'`,
+					`const someProps = {
   count: 0,
 }
 
@@ -1080,6 +1359,20 @@ import Widget2 from '../components/Widget2.astro';`},
 				code: `${$$maybeRenderHead($$result)}<main><script type="module">console.log("Hello");</script></main>`,
 			},
 		},
+		{
+			name:   "script importmap",
+			source: `<main><script type="importmap">{"imports":{}}</script></main>`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<main><script type="importmap">{"imports":{}}</script></main>`,
+			},
+		},
+		{
+			name:   "script module/shim",
+			source: `<main><script type="module/shim" src="es-shim.js"></script></main>`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<main><script type="module/shim" src="es-shim.js"></script></main>`,
+			},
+		},
 		{
 			name:             "script define:vars I",
 			staticExtraction: true,
@@ -1184,6 +1477,22 @@ ${$$renderComponent($$result,'Two',Two,{"client:load":true,"client:component-hyd
 ${$$renderComponent($$result,'my-element','my-element',{"client:load":true,"client:component-hydration":"load","client:component-path":($$metadata.getPath('my-element')),"client:component-export":($$metadata.getExport('my-element'))})}`,
 			},
 		},
+		{
+			name: "client:media passes its media query through",
+			source: `---
+import Comp from 'comp';
+---
+<Comp client:media="(min-width: 800px)" />`,
+			want: want{
+				frontmatter: []string{`import Comp from 'comp';`},
+				metadata: metadata{
+					modules:             []string{`{ module: $$module1, specifier: 'comp', assert: {} }`},
+					hydratedComponents:  []string{"Comp"},
+					hydrationDirectives: []string{"media"},
+				},
+				code: `${$$renderComponent($$result,'Comp',Comp,{"client:media":"(min-width: 800px)","client:component-hydration":"media","client:component-path":($$metadata.getPath(Comp)),"client:component-export":($$metadata.getExport(Comp))})}`,
+			},
+		},
 		{
 			name:   "Component siblings are siblings",
 			source: `<BaseHead></BaseHead><link href="test">`,
@@ -1298,6 +1607,37 @@ const article2 = await import('../markdown/article2.md')`},
 				code:   "${$$maybeRenderHead($$result)}<div></div>",
 			},
 		},
+		{
+			// The component function is always async, so top-level await needs no detection
+			name: "top-level await in frontmatter",
+			source: `---
+const data = await fetch('/api').then((res) => res.json())
+---
+<div>{data.title}</div>
+`, want: want{
+				frontmatter: []string{"", `const data = await fetch('/api').then((res) => res.json())`},
+				styles:      []string{},
+				code:        "${$$maybeRenderHead($$result)}<div>${data.title}</div>",
+			},
+		},
+		{
+			name: "await inside a nested async function",
+			source: `---
+async function load() {
+	const res = await fetch('/api');
+	return res.json();
+}
+---
+<div />
+`, want: want{
+				frontmatter: []string{"", `async function load() {
+	const res = await fetch('/api');
+	return res.json();
+}`},
+				styles: []string{},
+				code:   "${$$maybeRenderHead($$result)}<div></div>",
+			},
+		},
 		{
 			name: "Component names A-Z",
 			source: `---
@@ -1515,6 +1855,20 @@ import { Container, Col, Row } from 'react-bootstrap';
 				code: `${$$maybeRenderHead($$result)}<div class="something"${$$spreadAttributes(Astro.props,"Astro.props")}></div>`,
 			},
 		},
+		{
+			name:   "class:list with mixed array",
+			source: `<div class:list={["foo", isDark && "dark"]} />`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<div${$$addAttribute(["foo", isDark && "dark"], "class:list")}></div>`,
+			},
+		},
+		{
+			name:   "class:list with object",
+			source: `<div class:list={{ active: isActive }} />`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<div${$$addAttribute({ active: isActive }, "class:list")}></div>`,
+			},
+		},
 		{
 			name:   "class:list with spread",
 			source: `<div class:list="something" {...Astro.props} />`,
@@ -1529,6 +1883,13 @@ import { Container, Col, Row } from 'react-bootstrap';
 				code: `${$$maybeRenderHead($$result)}<div${$$spreadAttributes(Astro.props,"Astro.props")}></div>`,
 			},
 		},
+		{
+			name:   "spread object literal of data attributes",
+			source: `<div {...{'data-id': 5}} />`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<div${$$spreadAttributes({'data-id': 5},"{'data-id': 5}")}></div>`,
+			},
+		},
 		{
 			name:   "spread with style but no explicit class",
 			source: `<style>div { color: red; }</style><div {...Astro.props} />`,
@@ -1567,6 +1928,55 @@ import { Container, Col, Row } from 'react-bootstrap';
 				code: `${$$renderComponent($$result,'Fragment',Fragment,{},{"default": () => $$render` + BACKTICK + `world` + BACKTICK + `,})}`,
 			},
 		},
+		{
+			name: "Fragment imported from the runtime",
+			source: `---
+import { Fragment } from 'astro/jsx-runtime';
+---
+<Fragment><div>Hello</div></Fragment>`,
+			want: want{
+				frontmatter: []string{""},
+				code:        `${$$renderComponent($$result,'Fragment',Fragment,{},{"default": () => $$render` + BACKTICK + `${$$maybeRenderHead($$result)}<div>Hello</div>` + BACKTICK + `,})}`,
+			},
+		},
+		{
+			name: "Fragment imported from the runtime with other imports",
+			source: `---
+import { Fragment, jsx } from 'astro/jsx-runtime';
+---
+<Fragment><div>Hello</div></Fragment>`,
+			want: want{
+				frontmatter: []string{`import { jsx } from 'astro/jsx-runtime';`},
+				metadata:    metadata{modules: []string{`{ module: $$module1, specifier: 'astro/jsx-runtime', assert: {} }`}},
+				code:        `${$$renderComponent($$result,'Fragment',Fragment,{},{"default": () => $$render` + BACKTICK + `${$$maybeRenderHead($$result)}<div>Hello</div>` + BACKTICK + `,})}`,
+			},
+		},
+		{
+			name:   "MathML elements",
+			source: `<math><mi>x</mi><mo>+</mo><mn>1</mn></math>`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<math><mi>x</mi><mo>+</mo><mn>1</mn></math>`,
+			},
+		},
+		{
+			name:   "MathML elements slotted",
+			source: `<MathJax><math><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow></math></MathJax>`,
+			want: want{
+				code: `${$$renderComponent($$result,'MathJax',MathJax,{},{"default": () => $$render` + BACKTICK + `${$$maybeRenderHead($$result)}<math><mrow><mi>x</mi><mo>+</mo><mn>1</mn></mrow></math>` + BACKTICK + `,})}`,
+			},
+		},
+		{
+			name: "page rendered by a root layout component",
+			source: `---
+import Layout from '../layouts/Layout.astro';
+---
+<Layout><main>Hello</main></Layout>`,
+			want: want{
+				frontmatter: []string{`import Layout from '../layouts/Layout.astro';`},
+				metadata:    metadata{modules: []string{`{ module: $$module1, specifier: '../layouts/Layout.astro', assert: {} }`}},
+				code:        `${$$renderComponent($$result,'Layout',Layout,{},{"default": () => $$render` + BACKTICK + `${$$maybeRenderHead($$result)}<main>Hello</main>` + BACKTICK + `,})}`,
+			},
+		},
 		{
 			name:   "Fragment slotted",
 			source: `<body><Component><><div>Default</div><div>Named</div></></Component></body>`,
@@ -2033,49 +2443,105 @@ const items = ["Dog", "Cat", "Platipus"];
 			},
 		},
 		{
-			name:   "set:html",
-			source: "<article set:html={content} />",
+			name:   "static string expression",
+			source: `<p>{"<b>Tom & Jerry's</b>"}</p>`,
 			want: want{
-				code: `${$$maybeRenderHead($$result)}<article>${$$unescapeHTML(content)}</article>`,
+				code: `${$$maybeRenderHead($$result)}<p>&lt;b&gt;Tom &amp; Jerry&#39;s&lt;/b&gt;</p>`,
 			},
 		},
 		{
-			name:   "set:text",
-			source: "<article set:text={content} />",
+			name:   "static number expression",
+			source: `<p>{42}</p>`,
 			want: want{
-				code: `${$$maybeRenderHead($$result)}<article>${content}</article>`,
+				code: `${$$maybeRenderHead($$result)}<p>42</p>`,
 			},
 		},
 		{
-			name:   "set:html on Component",
-			source: "<Component set:html={content} />",
+			name:   "static template literal expression",
+			source: "<p>{`a & b`}</p>",
 			want: want{
-				code: `${$$renderComponent($$result,'Component',Component,{},{"default": () => $$render` + "`${$$unescapeHTML(content)}`," + `})}`,
+				code: `${$$maybeRenderHead($$result)}<p>a &amp; b</p>`,
 			},
 		},
 		{
-			name:   "set:text on Component",
-			source: "<Component set:text={content} />",
+			name:   "template literal expression with substitutions",
+			source: "<p>{`a ${b}`}</p>",
 			want: want{
-				code: `${$$renderComponent($$result,'Component',Component,{},{"default": () => $$render` + "`${content}`," + `})}`,
+				code: "${$$maybeRenderHead($$result)}<p>${`a ${b}`}</p>",
 			},
 		},
 		{
-			name:   "set:html on custom-element",
-			source: "<custom-element set:html={content} />",
+			name:   "string literal expression with escapes",
+			source: `<p>{"a\nb"}</p>`,
 			want: want{
-				code: `${$$renderComponent($$result,'custom-element','custom-element',{},{"default": () => $$render` + "`${$$unescapeHTML(content)}`," + `})}`,
+				code: `${$$maybeRenderHead($$result)}<p>${"a\nb"}</p>`,
 			},
 		},
 		{
-			name:   "set:text on custom-element",
-			source: "<custom-element set:text={content} />",
+			name:   "static attribute expression",
+			source: `<a href={"/a?b=1&c=2"}>Home</a>`,
 			want: want{
-				code: `${$$renderComponent($$result,'custom-element','custom-element',{},{"default": () => $$render` + "`${content}`," + `})}`,
+				code: `${$$maybeRenderHead($$result)}<a href="/a?b=1&amp;c=2">Home</a>`,
 			},
 		},
 		{
-			name:   "set:html on self-closing tag",
+			name:   "set:html with a string literal",
+			source: `<div set:html={"<b>hi</b>"} />`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<div>${$$unescapeHTML("<b>hi</b>")}</div>`,
+			},
+		},
+		{
+			name:   "set:text with a string literal",
+			source: `<div set:text={"<b>hi</b>"} />`,
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<div>&lt;b&gt;hi&lt;/b&gt;</div>`,
+			},
+		},
+		{
+			name:   "set:html",
+			source: "<article set:html={content} />",
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<article>${$$unescapeHTML(content)}</article>`,
+			},
+		},
+		{
+			name:   "set:text",
+			source: "<article set:text={content} />",
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<article>${content}</article>`,
+			},
+		},
+		{
+			name:   "set:html on Component",
+			source: "<Component set:html={content} />",
+			want: want{
+				code: `${$$renderComponent($$result,'Component',Component,{},{"default": () => $$render` + "`${$$unescapeHTML(content)}`," + `})}`,
+			},
+		},
+		{
+			name:   "set:text on Component",
+			source: "<Component set:text={content} />",
+			want: want{
+				code: `${$$renderComponent($$result,'Component',Component,{},{"default": () => $$render` + "`${content}`," + `})}`,
+			},
+		},
+		{
+			name:   "set:html on custom-element",
+			source: "<custom-element set:html={content} />",
+			want: want{
+				code: `${$$renderComponent($$result,'custom-element','custom-element',{},{"default": () => $$render` + "`${$$unescapeHTML(content)}`," + `})}`,
+			},
+		},
+		{
+			name:   "set:text on custom-element",
+			source: "<custom-element set:text={content} />",
+			want: want{
+				code: `${$$renderComponent($$result,'custom-element','custom-element',{},{"default": () => $$render` + "`${content}`," + `})}`,
+			},
+		},
+		{
+			name:   "set:html on self-closing tag",
 			source: "<article set:html={content} />",
 			want: want{
 				code: `${$$maybeRenderHead($$result)}<article>${$$unescapeHTML(content)}</article>`,
@@ -2095,6 +2561,14 @@ const items = ["Dog", "Cat", "Platipus"];
 				code: `${$$maybeRenderHead($$result)}<article>${$$unescapeHTML(content)}</article>`,
 			},
 		},
+		{
+			// The component function is always async, so awaiting in the directive is fine
+			name:   "set:html with await",
+			source: "<div set:html={await getHtml()} />",
+			want: want{
+				code: `${$$maybeRenderHead($$result)}<div>${$$unescapeHTML(await getHtml())}</div>`,
+			},
+		},
 		{
 			// If both "set:*" directives are passed, we only respect the first one
 			name:   "set:html and set:text",
@@ -2221,7 +2695,7 @@ const items = ["Dog", "Cat", "Platipus"];
 			}
 
 			hash := astro.HashFromSource(code)
-			transform.ExtractStyles(doc)
+			transform.ExtractStyles(doc, &transform.TransformOptions{})
 			transform.Transform(doc, transform.TransformOptions{Scope: hash}) // note: we want to test Transform in context here, but more advanced cases could be tested separately
 			result := PrintToJS(code, doc, 0, transform.TransformOptions{
 				Scope:            "XXXX",
@@ -2243,7 +2717,7 @@ const items = ["Dog", "Cat", "Platipus"];
 					spec := moduleSpecRe.FindSubmatch([]byte(m)) // 0: full match, 1: submatch
 					asrt := ""
 					if string(spec[2]) != "{}" {
-						asrt = " assert " + string(spec[2])
+						asrt = " with " + string(spec[2])
 					}
 					toMatch += fmt.Sprintf("import * as $$module%s from %s%s;\n", strconv.Itoa(i+1), string(spec[1]), asrt)
 				}
@@ -2304,7 +2778,7 @@ const items = ["Dog", "Cat", "Platipus"];
 					metadata += h
 				}
 			}
-			metadata += "] }"
+			metadata += "], scope: 'XXXX' }"
 
 			toMatch += "\n\n" + fmt.Sprintf("export const %s = %s(import.meta.url, %s);\n\n", METADATA, CREATE_METADATA, metadata)
 			toMatch += test_utils.Dedent(CREATE_ASTRO_CALL) + "\n\n"
@@ -2450,3 +2924,860 @@ const c = '\''
 		})
 	}
 }
+
+// Prints the source with the given options, for tests that need options the
+// TestPrinter harness doesn't cover
+func printWithOptions(t *testing.T, source string, opts transform.TransformOptions) string {
+	t.Helper()
+	code := test_utils.Dedent(source)
+	doc, err := astro.Parse(strings.NewReader(code))
+	if err != nil {
+		t.Error(err)
+	}
+	if opts.Scope == "" {
+		opts.Scope = "XXXX"
+	}
+	transform.ExtractStyles(doc, &opts)
+	transform.Transform(doc, opts)
+	result := PrintToJS(code, doc, 0, opts)
+	return string(result.Output)
+}
+
+func TestPrintHMR(t *testing.T) {
+	source := `<div>Hello</div>`
+	hmr := "if (import.meta.hot) {\n  import.meta.hot.data.$$Component = $$Component;\n  import.meta.hot.accept();\n}\n"
+
+	output := printWithOptions(t, source, transform.TransformOptions{HMR: true})
+	if !strings.HasSuffix(output, "export default $$Component;\n"+hmr) {
+		t.Errorf("expected HMR code after the default export, got:\n%s", output)
+	}
+
+	output = printWithOptions(t, source, transform.TransformOptions{})
+	if strings.Contains(output, "import.meta.hot") {
+		t.Errorf("expected no HMR code without the hmr option, got:\n%s", output)
+	}
+}
+
+func TestPrintStripEventHandlers(t *testing.T) {
+	source := `<button onclick={fn} onmouseover="alert()" type="button">Click</button><Component onClick={fn} />`
+
+	output := printWithOptions(t, source, transform.TransformOptions{StripEventHandlers: true})
+	if !strings.Contains(output, "<button type=\"button\">Click</button>") {
+		t.Errorf("expected event handlers to be stripped, got:\n%s", output)
+	}
+	if !strings.Contains(output, "{\"onClick\":(fn)}") {
+		t.Errorf("expected component props to be kept, got:\n%s", output)
+	}
+
+	output = printWithOptions(t, source, transform.TransformOptions{})
+	if !strings.Contains(output, "onmouseover=\"alert()\"") || !strings.Contains(output, "$$addAttribute(fn, \"onclick\")") {
+		t.Errorf("expected event handlers without the stripEventHandlers option, got:\n%s", output)
+	}
+}
+
+func TestPrintEmptyFrontmatterBody(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+	}{
+		{
+			name:   "no frontmatter",
+			source: `<div />`,
+		},
+		{
+			name:   "empty frontmatter",
+			source: "---\n---\n<div />",
+		},
+		{
+			name:   "import-only frontmatter",
+			source: "---\nimport A from './A.astro';\n---\n<A />",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			output := printWithOptions(t, tt.source, transform.TransformOptions{})
+			// Exactly one blank line between the component prelude and the return statement
+			if !strings.Contains(output, "Astro.self = $$Component;\n\n"+RETURN) {
+				t.Errorf("expected a single blank line before the return statement, got:\n%s", output)
+			}
+		})
+	}
+}
+
+func TestPrintSlotComments(t *testing.T) {
+	tests := []struct {
+		name     string
+		source   string
+		stripped string
+		kept     string
+	}{
+		{
+			name:     "before a named slot",
+			source:   `<Component><!-- why --><div slot="a">A</div></Component>`,
+			stripped: "{\"a\": () => $$render`${$$maybeRenderHead($$result)}<div>A</div>`,}",
+			kept:     "{\"a\": () => $$render`<!-- why -->${$$maybeRenderHead($$result)}<div>A</div>`,}",
+		},
+		{
+			name:     "between named slots",
+			source:   `<Component><div slot="a">A</div><!-- b --><div slot="b">B</div></Component>`,
+			stripped: "{\"a\": () => $$render`${$$maybeRenderHead($$result)}<div>A</div>`,\"b\": () => $$render`<div>B</div>`,}",
+			kept:     "{\"a\": () => $$render`${$$maybeRenderHead($$result)}<div>A</div>`,\"b\": () => $$render`<!-- b --><div>B</div>`,}",
+		},
+		{
+			name:     "at the end",
+			source:   `<Component><div slot="a">A</div><!-- end --></Component>`,
+			stripped: "{\"a\": () => $$render`${$$maybeRenderHead($$result)}<div>A</div>`,}",
+			kept:     "{\"a\": () => $$render`${$$maybeRenderHead($$result)}<div>A</div><!-- end -->`,}",
+		},
+		{
+			name:     "after a self-closing named slot",
+			source:   `<Component><div slot="a"/><!-- c --></Component>`,
+			stripped: "{\"a\": () => $$render`${$$maybeRenderHead($$result)}<div></div>`,}",
+			kept:     "{\"a\": () => $$render`${$$maybeRenderHead($$result)}<div></div><!-- c -->`,}",
+		},
+		{
+			name:     "after the default slot",
+			source:   `<Component><div>A</div><!-- end --></Component>`,
+			stripped: "{\"default\": () => $$render`${$$maybeRenderHead($$result)}<div>A</div>`,}",
+			kept:     "{\"default\": () => $$render`${$$maybeRenderHead($$result)}<div>A</div><!-- end -->`,}",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			output := printWithOptions(t, tt.source, transform.TransformOptions{})
+			if !strings.Contains(output, tt.stripped) {
+				t.Errorf("expected comments to be stripped, got:\n%s", output)
+			}
+			output = printWithOptions(t, tt.source, transform.TransformOptions{PreserveSlotComments: true})
+			if !strings.Contains(output, tt.kept) {
+				t.Errorf("expected comments to be kept, got:\n%s", output)
+			}
+		})
+	}
+}
+
+func TestPrintDefine(t *testing.T) {
+	source := "---\nconst Layout = import.meta.env.SSR ? ServerLayout : ClientLayout;\n---\n<a href={import.meta.env.PUBLIC_URL}>{import.meta.env.SSR && <span>SSR</span>}</a><img src=`${import.meta.env.PUBLIC_URL}logo.svg` alt=`import.meta.env.SSR` /><div {...import.meta.env.SSR} />"
+	define := map[string]string{
+		"import.meta.env.SSR":        "false",
+		"import.meta.env.PUBLIC_URL": `"/"`,
+	}
+
+	output := printWithOptions(t, source, transform.TransformOptions{Define: define})
+	for _, want := range []string{
+		"const Layout = false ? ServerLayout : ClientLayout;",
+		`<a href="/">`,
+		"${false && $$render`<span>SSR</span>`}",
+		"${$$addAttribute(`${\"/\"}logo.svg`, \"src\")}",
+		"${$$spreadAttributes(false,",
+	} {
+		if !strings.Contains(output, want) {
+			t.Errorf("expected %s in output, got:\n%s", want, output)
+		}
+	}
+	// Text outside of `${}` in a template literal isn't code
+	if strings.Count(output, "import.meta.env") != 1 || !strings.Contains(output, "`import.meta.env.SSR`") {
+		t.Errorf("expected all defines to be replaced, got:\n%s", output)
+	}
+}
+
+// Trailing whitespace-only text is dropped from the end of the template
+// (see `transform.TrimTrailingSpace`) and from every slot callback body.
+func TestPrintTrailingWhitespace(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "template body",
+			source: "<div>A</div>\n\n\t\n",
+			want:   "<div>A</div>`;",
+		},
+		{
+			name:   "default slot",
+			source: "<Component>\n  <div>A</div>\n\n  </Component>",
+			want:   "{\"default\": () => $$render`${$$maybeRenderHead($$result)}<div>A</div>`,}",
+		},
+		{
+			name:   "named slot",
+			source: "<Component>\n  <div slot=\"a\">A</div>\n\n  </Component>",
+			want:   "{\"a\": () => $$render`${$$maybeRenderHead($$result)}<div>A</div>`,}",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			output := printWithOptions(t, tt.source, transform.TransformOptions{})
+			if !strings.Contains(output, tt.want) {
+				t.Errorf("expected %s in output, got:\n%s", tt.want, output)
+			}
+		})
+	}
+}
+
+func TestPrintComponentName(t *testing.T) {
+	tests := []struct {
+		pathname string
+		want     string
+	}{
+		{"", "$$Component"},
+		{"src/pages/blog/PostDetail.astro", "$$PostDetail"},
+		{"src/pages/index.astro", "$$Index"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.pathname, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(`<div />`))
+			if err != nil {
+				t.Error(err)
+			}
+			opts := transform.TransformOptions{Scope: "XXXX", Pathname: tt.pathname}
+			transform.Transform(doc, opts)
+			result := PrintToJS(`<div />`, doc, 0, opts)
+			if tt.want != result.ComponentName {
+				t.Errorf("want: %s\n  got:  %s", tt.want, result.ComponentName)
+			}
+			if !strings.Contains(string(result.Output), "export default "+tt.want+";") {
+				t.Errorf("expected the default export to use %s, got:\n%s", tt.want, result.Output)
+			}
+		})
+	}
+}
+
+func TestPrintInlineScriptThreshold(t *testing.T) {
+	small := `<script>alert(1)</script><div />`
+	module := `<script>import { a } from "./a.js"; a()</script><div />`
+	large := `<script>console.log("this script is longer than the threshold")</script><div />`
+	opts := transform.TransformOptions{InlineScriptThreshold: 20}
+
+	output := printWithOptions(t, small, opts)
+	if !strings.Contains(output, `<script type="module">alert(1)</script>`) || strings.Contains(output, "const SCRIPTS") {
+		t.Errorf("expected the small script to stay inline as a module, got:\n%s", output)
+	}
+
+	output = printWithOptions(t, module, transform.TransformOptions{InlineScriptThreshold: 100})
+	if !strings.Contains(output, `<script type="module">import { a } from "./a.js"; a()</script>`) {
+		t.Errorf("expected the small script with an import to stay inline as a module, got:\n%s", output)
+	}
+
+	output = printWithOptions(t, large, opts)
+	if strings.Contains(output, "<script>console.log") || !strings.Contains(output, "const SCRIPTS") {
+		t.Errorf("expected the large script to be hoisted, got:\n%s", output)
+	}
+
+	output = printWithOptions(t, small, transform.TransformOptions{})
+	if strings.Contains(output, "alert(1)</script>") {
+		t.Errorf("expected scripts to be hoisted without a threshold, got:\n%s", output)
+	}
+}
+
+func TestPrintOutputExtension(t *testing.T) {
+	tests := []struct {
+		name string
+		ext  string
+		want string
+	}{
+		{name: "default", ext: "", want: `import "Component.astro?astro&type=style&index=0&lang.css";`},
+		{name: "css", ext: "css", want: `import "Component.astro?astro&type=style&index=0&lang.css";`},
+		{name: "scss", ext: "scss", want: `import "Component.astro?astro&type=style&index=0&lang.scss";`},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			code := `<style>div { color: red; }</style><div />`
+			doc, err := astro.Parse(strings.NewReader(code))
+			if err != nil {
+				t.Error(err)
+			}
+			opts := transform.TransformOptions{Scope: "XXXX", Filename: "Component.astro", StaticExtraction: true, OutputExtension: tt.ext}
+			transform.ExtractStyles(doc, &opts)
+			transform.Transform(doc, opts)
+			output := string(PrintToJS(code, doc, 1, opts).Output)
+			if !strings.Contains(output, tt.want) {
+				t.Errorf("expected %s, got:\n%s", tt.want, output)
+			}
+		})
+	}
+}
+
+func TestPrintStyleLangImports(t *testing.T) {
+	code := `<style lang="scss">div { color: red; }</style><style lang="less"></style><style>p { color: blue; }</style><div />`
+	doc, err := astro.Parse(strings.NewReader(code))
+	if err != nil {
+		t.Error(err)
+	}
+	opts := transform.TransformOptions{Scope: "XXXX", Filename: "Component.astro", StaticExtraction: true}
+	transform.ExtractStyles(doc, &opts)
+	transform.Transform(doc, opts)
+	output := string(PrintToJS(code, doc, 2, opts).Output)
+	// Styles are indexed in the same order as the STYLES array, and the empty <style> isn't printed
+	want := `import "Component.astro?astro&type=style&index=0&lang.css";import "Component.astro?astro&type=style&index=1&lang.scss";`
+	if !strings.Contains(output, want) {
+		t.Errorf("expected %s, got:\n%s", want, output)
+	}
+}
+
+func TestPrintDisableRuntimeImports(t *testing.T) {
+	code := `<style>h1 { color: red; }</style><h1>Hello</h1>`
+	printOutput := func(disable bool) string {
+		doc, err := astro.Parse(strings.NewReader(code))
+		if err != nil {
+			t.Error(err)
+		}
+		opts := transform.TransformOptions{Scope: "XXXX", InternalURL: "astro/runtime/server/index.js", StaticExtraction: true, DisableRuntimeImports: disable}
+		transform.ExtractStyles(doc, &opts)
+		transform.Transform(doc, opts)
+		return string(PrintToJS(code, doc, 1, opts).Output)
+	}
+
+	output := printOutput(false)
+	if !strings.Contains(output, `from "astro/runtime/server/index.js";`) || !strings.Contains(output, "?astro&type=style") {
+		t.Errorf("expected runtime and style imports by default, got:\n%s", output)
+	}
+
+	output = printOutput(true)
+	if strings.Contains(output, `from "astro/runtime/server/index.js";`) || strings.Contains(output, "?astro&type=style") {
+		t.Errorf("expected no runtime or style imports, got:\n%s", output)
+	}
+	if !strings.Contains(output, "const $$Component = "+CREATE_COMPONENT+"(") {
+		t.Errorf("expected the component to still be created, got:\n%s", output)
+	}
+}
+
+func TestPrintLargeSingleLine(t *testing.T) {
+	if testing.Short() {
+		t.Skip("skipping large input in short mode")
+	}
+	// ~20 MB of minified markup on a single line
+	code := "<div>" + strings.Repeat(`<p class="a">lorem ipsum dolor sit amet</p>`, 480000) + "</div>"
+	doc, err := astro.Parse(strings.NewReader(code))
+	if err != nil {
+		t.Error(err)
+	}
+	opts := transform.TransformOptions{Scope: "XXXX", SourceMap: "both"}
+	transform.ExtractStyles(doc, &opts)
+	transform.Transform(doc, opts)
+	result := PrintToJS(code, doc, 0, opts)
+	if !strings.HasSuffix(string(result.Output), "export default $$Component;\n") {
+		t.Errorf("expected the full component to be printed")
+	}
+	if len(result.SourceMapChunk.Buffer) == 0 {
+		t.Errorf("expected source mappings to be generated")
+	}
+}
+
+// Generates small random templates from a grammar of elements, components,
+// attributes and expressions, and checks invariants of the printed output
+// that are easy to break when printing attributes or slots
+func TestPrintGeneratedTemplates(t *testing.T) {
+	attrs := []string{`a="b"`, `a={value}`, `{value}`, `{...props}`, `a`, "a=`b`"}
+	var generate func(r *rand.Rand, depth int, components *int) string
+	generate = func(r *rand.Rand, depth int, components *int) string {
+		out := ""
+		for i := r.Intn(3) + 1; i > 0; i-- {
+			kind := r.Intn(5)
+			if depth > 3 {
+				kind = r.Intn(2)
+			}
+			tag := []string{"div", "span"}[r.Intn(2)]
+			if kind >= 3 {
+				tag = []string{"Component", "Card"}[r.Intn(2)]
+				*components++
+			}
+			attr := ""
+			for j := r.Intn(3); j > 0; j-- {
+				attr += " " + attrs[r.Intn(len(attrs))]
+			}
+			switch kind {
+			case 0:
+				out += "hello"
+			case 1:
+				out += "{value}"
+			case 2, 3:
+				out += fmt.Sprintf("<%s%s>%s</%s>", tag, attr, generate(r, depth+1, components), tag)
+			case 4:
+				out += fmt.Sprintf("<%s%s />", tag, attr)
+			}
+		}
+		return out
+	}
+
+	for i := 0; i < 200; i++ {
+		components := 0
+		source := generate(rand.New(rand.NewSource(int64(i))), 0, &components)
+		output := printWithOptions(t, source, transform.TransformOptions{})
+		if strings.Count(output, "{") != strings.Count(output, "}") {
+			t.Errorf("unbalanced braces for %s, got:\n%s", source, output)
+		}
+		if strings.Count(output, "`")%2 != 0 {
+			t.Errorf("unbalanced template literals for %s, got:\n%s", source, output)
+		}
+		if got := strings.Count(output, RENDER_COMPONENT+"($$result,"); got != components {
+			t.Errorf("expected %d components for %s, got %d:\n%s", components, source, got, output)
+		}
+	}
+}
+
+func TestPrintBackslashes(t *testing.T) {
+	source := `<p title="C:\path \u1234">C:\path \u1234 \n trailing\</p>
+<!-- \u1234 -->
+<Component title='say "\u1234"' />
+<script>console.log("\u1234");</script>
+<script src="C:\scripts\app.js"></script>`
+	output := printWithOptions(t, source, transform.TransformOptions{StaticExtraction: true})
+
+	for _, want := range []string{
+		`<p title="C:\\path \\u1234">C:\\path \\u1234 \\n trailing\\</p>`,
+		`<!-- \\u1234 -->`,
+		`{"title":"say \"\\u1234\""}`,
+		`{ type: 'inline', value: ` + BACKTICK + `console.log("\\u1234");` + BACKTICK + ` }`,
+		`{ type: 'external', src: 'C:\\scripts\\app.js' }`,
+	} {
+		if !strings.Contains(output, want) {
+			t.Errorf("expected %s, got:\n%s", want, output)
+		}
+	}
+}
+
+func TestPrintJSXImportSource(t *testing.T) {
+	source := `<div>{items.map(item => <span>{item}</span>)}</div>`
+
+	output := printWithOptions(t, source, transform.TransformOptions{JSXImportSource: "preact"})
+	if !strings.HasPrefix(output, "/* @jsxImportSource preact */\nimport {") {
+		t.Errorf("expected the pragma before the runtime imports, got:\n%s", output)
+	}
+
+	output = printWithOptions(t, source, transform.TransformOptions{})
+	if strings.Contains(output, "@jsxImportSource") {
+		t.Errorf("expected no pragma by default, got:\n%s", output)
+	}
+}
+
+func TestPrintImportSpecifierEscaping(t *testing.T) {
+	tests := []struct {
+		name        string
+		internalURL string
+		want        string
+	}{
+		{
+			name:        "spaces",
+			internalURL: "/my project/astro internal.js",
+			want:        `} from "/my project/astro internal.js";`,
+		},
+		{
+			name:        "double quote",
+			internalURL: `/a"b/internal.js`,
+			want:        `} from "/a\"b/internal.js";`,
+		},
+		{
+			name:        "backslash",
+			internalURL: `C:\astro\internal.js`,
+			want:        `} from "C:\\astro\\internal.js";`,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			output := printWithOptions(t, `<div />`, transform.TransformOptions{InternalURL: tt.internalURL})
+			if !strings.Contains(output, tt.want) {
+				t.Errorf("expected %s, got:\n%s", tt.want, output)
+			}
+		})
+	}
+}
+
+// Decodes the "mappings" of a printed chunk so positions can be looked up with Find
+func decodeMappings(buffer []byte) sourcemap.SourceMap {
+	sm := sourcemap.SourceMap{}
+	state := sourcemap.Mapping{}
+	for i := 0; i < len(buffer); {
+		switch buffer[i] {
+		case ';':
+			state.GeneratedLine++
+			state.GeneratedColumn = 0
+			i++
+			continue
+		case ',':
+			i++
+			continue
+		}
+		var delta int
+		delta, i = sourcemap.DecodeVLQ(buffer, i)
+		state.GeneratedColumn += delta
+		delta, i = sourcemap.DecodeVLQ(buffer, i)
+		state.SourceIndex += delta
+		delta, i = sourcemap.DecodeVLQ(buffer, i)
+		state.OriginalLine += delta
+		delta, i = sourcemap.DecodeVLQ(buffer, i)
+		state.OriginalColumn += delta
+		sm.Mappings = append(sm.Mappings, state)
+	}
+	return sm
+}
+
+// Returns the 0-based line and column of an index into ASCII text
+func lineAndColumn(text string, index int) (int, int) {
+	line := strings.Count(text[:index], "\n")
+	return line, index - (strings.LastIndex(text[:index], "\n") + 1)
+}
+
+func TestPrintSourceMapPositions(t *testing.T) {
+	code := "---\nconst title = \"Astro\";\n---\n<h1>Hello {title}</h1>\n"
+	doc, err := astro.Parse(strings.NewReader(code))
+	if err != nil {
+		t.Error(err)
+	}
+	opts := transform.TransformOptions{Scope: "XXXX", SourceMap: "external"}
+	transform.ExtractStyles(doc, &opts)
+	transform.Transform(doc, opts)
+	result := PrintToJS(code, doc, 0, opts)
+	output := string(result.Output)
+	sm := decodeMappings(result.SourceMapChunk.Buffer)
+
+	find := func(generated int) *sourcemap.Mapping {
+		line, column := lineAndColumn(output, generated)
+		return sm.Find(line, column)
+	}
+
+	tests := []struct {
+		name      string
+		generated int
+		original  int
+	}{
+		{
+			name:      "text",
+			generated: strings.Index(output, "Hello"),
+			original:  strings.Index(code, "Hello"),
+		},
+		{
+			name:      "expression",
+			generated: strings.Index(output, "${title}") + len("${"),
+			original:  strings.Index(code, "{title}") + len("{"),
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if tt.generated < 0 || tt.original < 0 {
+				t.Fatalf("expected to find the text in the output:\n%s", output)
+			}
+			mapping := find(tt.generated)
+			if mapping == nil {
+				t.Fatalf("expected a mapping at %d", tt.generated)
+			}
+			line, column := lineAndColumn(code, tt.original)
+			if mapping.OriginalLine != line || mapping.OriginalColumn != column {
+				t.Errorf("expected %d:%d, got %d:%d", line, column, mapping.OriginalLine, mapping.OriginalColumn)
+			}
+		})
+	}
+
+	// The frontmatter is mapped as a single block, so references only resolve to somewhere inside of it
+	mapping := find(strings.Index(output, "const title"))
+	if mapping == nil || mapping.OriginalLine > 1 {
+		t.Errorf("expected the frontmatter to map into the frontmatter, got %v", mapping)
+	}
+}
+
+func TestPrintLargeAttributeValue(t *testing.T) {
+	// ~5 MB of base64, which contains nothing that needs escaping
+	value := "data:image/png;base64," + strings.Repeat("iVBORw0KGgoAAAANSUhEUgAAAAEAAAAB+/=", 150000)
+	output := printWithOptions(t, `<img src="`+value+`" />`, transform.TransformOptions{})
+	if !strings.Contains(output, `<img src="`+value+`">`) {
+		t.Errorf("expected the attribute value to be printed unchanged")
+	}
+
+	allocs := testing.AllocsPerRun(10, func() {
+		encodeDoubleQuote(escapeText(value))
+	})
+	if allocs != 0 {
+		t.Errorf("expected no allocations when escaping a value without special characters, got %v", allocs)
+	}
+}
+
+func TestPrintNormalizedFilename(t *testing.T) {
+	code := `<style>div { color: red; }</style><script>console.log("hi")</script><div />`
+	doc, err := astro.Parse(strings.NewReader(code))
+	if err != nil {
+		t.Error(err)
+	}
+	filename := "/home/user/site/src/pages/index.astro"
+	opts := transform.TransformOptions{
+		Scope:              "XXXX",
+		Filename:           filename,
+		NormalizedFilename: transform.NormalizeFilename(filename, "/home/user/site"),
+		StaticExtraction:   true,
+	}
+	transform.ExtractStyles(doc, &opts)
+	transform.Transform(doc, opts)
+	output := string(PrintToJS(code, doc, 1, opts).Output)
+	want := `import "/src/pages/index.astro?astro&type=style&index=0&lang.css";`
+	if !strings.Contains(output, want) {
+		t.Errorf("expected %s, got:\n%s", want, output)
+	}
+	if strings.Contains(output, "/home/user") {
+		t.Errorf("expected no absolute paths in the output, got:\n%s", output)
+	}
+}
+
+func TestPrintEmitCSSInline(t *testing.T) {
+	source := `<style>div{color:red}</style><style is:global>p{color:blue}</style><div />`
+	output := printWithOptions(t, source, transform.TransformOptions{Filename: "Component.astro", StaticExtraction: true, EmitCSSInline: true})
+	want := "`<style>div:where(.astro-XXXX){color:red}</style><style>p{color:blue}</style>"
+	if !strings.Contains(output, want) {
+		t.Errorf("expected the styles at the start of the template, got:\n%s", output)
+	}
+	if !strings.Contains(output, `<div class="astro-XXXX">`) {
+		t.Errorf("expected elements to still be scoped, got:\n%s", output)
+	}
+	if strings.Contains(output, "const STYLES") || strings.Contains(output, "?astro&type=style") {
+		t.Errorf("expected no STYLES array or style imports, got:\n%s", output)
+	}
+}
+
+func TestPrintEmitCSSInlinePage(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "explicit head",
+			source: `<!DOCTYPE html><html><head><title>A</title></head><body><div /></body></html><style>div{color:red}</style>`,
+			want:   "<title>A</title><style>div:where(.astro-XXXX){color:red}</style>${$$renderHead($$result)}</head>",
+		},
+		{
+			name:   "implicit head",
+			source: `<html><body><div /></body></html><style>div{color:red}</style>`,
+			want:   "<style>div:where(.astro-XXXX){color:red}</style>${$$maybeRenderHead($$result)}<body",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			output := printWithOptions(t, tt.source, transform.TransformOptions{Filename: "Page.astro", StaticExtraction: true, EmitCSSInline: true})
+			if !strings.Contains(output, tt.want) {
+				t.Errorf("expected the styles in <head>, got:\n%s", output)
+			}
+			if strings.Index(output, "<style>") < strings.Index(output, "<html") {
+				t.Errorf("expected no styles before <html>, got:\n%s", output)
+			}
+			if strings.Count(output, "<style>") != 1 {
+				t.Errorf("expected the styles to be printed once, got:\n%s", output)
+			}
+		})
+	}
+}
+
+func TestPrintModules(t *testing.T) {
+	code := `---
+import Counter from '../components/Counter.jsx';
+import type { Props } from '../types';
+import '../styles/global.css';
+import data from './data.json' with { type: 'json' };
+---
+<Counter />`
+	doc, err := astro.Parse(strings.NewReader(code))
+	if err != nil {
+		t.Error(err)
+	}
+	opts := transform.TransformOptions{Scope: "XXXX"}
+	transform.Transform(doc, opts)
+	modules := PrintToJS(code, doc, 0, opts).Modules
+	want := []ModuleImport{
+		{Specifier: "../components/Counter.jsx"},
+		{Specifier: "../types", IsTypeOnly: true},
+		{Specifier: "../styles/global.css", IsCSSImport: true},
+		{Specifier: "./data.json", Attributes: map[string]string{"type": "json"}},
+	}
+	if len(modules) != len(want) {
+		t.Fatalf("expected %d modules, got %+v", len(want), modules)
+	}
+	for i, m := range modules {
+		w := want[i]
+		if m.Specifier != w.Specifier || m.IsCSSImport != w.IsCSSImport || m.IsTypeOnly != w.IsTypeOnly || len(m.Attributes) != len(w.Attributes) {
+			t.Errorf("expected %+v, got %+v", w, m)
+		}
+		for key, value := range w.Attributes {
+			if m.Attributes[key] != value {
+				t.Errorf("expected attribute %s of %s to be %q, got %q", key, m.Specifier, value, m.Attributes[key])
+			}
+		}
+	}
+}
+
+func TestPrintScopedClass(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "static class",
+			source: `<div class="foo" />`,
+			want:   `<div class="foo astro-XXXX"></div>`,
+		},
+		{
+			name:   "dynamic class",
+			source: `<div class={dynamicClass} />`,
+			want:   `<div${$$addAttribute(((dynamicClass) ?? "") + " astro-XXXX", "class")}></div>`,
+		},
+		{
+			name:   "no class",
+			source: `<div />`,
+			want:   `<div class="astro-XXXX"></div>`,
+		},
+		{
+			name:   "spread",
+			source: `<div {...props} />`,
+			want:   `<div${$$spreadAttributes(props,"props",{"class":"astro-XXXX"})}></div>`,
+		},
+		{
+			name:   "spread with class",
+			source: `<div {...props} class="foo" />`,
+			want:   `<div${$$spreadAttributes(props,"props")} class="foo astro-XXXX"></div>`,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			output := printWithOptions(t, "<style>div { color: red; }</style>"+tt.source, transform.TransformOptions{})
+			if !strings.Contains(output, tt.want) {
+				t.Errorf("expected %s, got:\n%s", tt.want, output)
+			}
+			if strings.Count(output, "class") != strings.Count(tt.want, "class") {
+				t.Errorf("expected a single class attribute, got:\n%s", output)
+			}
+		})
+	}
+}
+
+func TestPrintScopedComponentClass(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "static class",
+			source: `<Component class="foo" />`,
+			want:   `{"class":"foo astro-XXXX"}`,
+		},
+		{
+			name:   "dynamic class",
+			source: `<Component class={expr} />`,
+			want:   `{"class":(((expr) ?? "") + " astro-XXXX")}`,
+		},
+		{
+			name:   "no class",
+			source: `<Component />`,
+			want:   `{"class":"astro-XXXX"}`,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			output := printWithOptions(t, "<style>div { color: red; }</style>"+tt.source, transform.TransformOptions{})
+			if !strings.Contains(output, tt.want) {
+				t.Errorf("expected %s, got:\n%s", tt.want, output)
+			}
+			if strings.Count(output, `"class"`) != 1 {
+				t.Errorf("expected a single class key, got:\n%s", output)
+			}
+		})
+	}
+}
+
+func TestPrintScopedSlotNames(t *testing.T) {
+	source := `<Component><div slot="fallback">Loading</div><p>Hello</p>{cond ? <span slot="fallback">A</span> : <span slot="other">B</span>}</Component>`
+	output := printWithOptions(t, source, transform.TransformOptions{ScopedSlotNames: []string{"fallback"}})
+	for _, want := range []string{
+		"\"fallback\": ($$result) => $$render`",
+		"\"default\": () => $$render`",
+		"{\"fallback\": ($$result) => ",
+		"{\"other\": () => ",
+	} {
+		if !strings.Contains(output, want) {
+			t.Errorf("expected %s, got:\n%s", want, output)
+		}
+	}
+
+	output = printWithOptions(t, source, transform.TransformOptions{})
+	if strings.Contains(output, "($$result) =>") {
+		t.Errorf("expected no scoped slots by default, got:\n%s", output)
+	}
+}
+
+func TestPrintDefineVarsWithAwait(t *testing.T) {
+	source := `---
+import { getData } from '../data';
+---
+<style define:vars={{ data: await getData() }}>h1 { color: var(--data); }</style>
+<h1>Hello</h1>`
+	output := printWithOptions(t, source, transform.TransformOptions{StaticExtraction: true})
+	// The component function is always async, so the awaited value can be used as-is
+	if !strings.Contains(output, "$$createComponent(async ($$result, $$props, $$slots) => {") {
+		t.Errorf("expected an async component, got:\n%s", output)
+	}
+	want := "const $$definedVars = $$defineStyleVars([{ data: await getData() }]);"
+	if !strings.Contains(output, want) {
+		t.Errorf("expected %s, got:\n%s", want, output)
+	}
+	if strings.Index(output, want) < strings.Index(output, "$$createComponent(") {
+		t.Errorf("expected the defined vars inside of the component function, got:\n%s", output)
+	}
+}
+
+func TestPrintDuplicateHead(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+	}{
+		{
+			name:   "nested head",
+			source: `<html><head><title>A</title></head><body><Layout><head><meta charset="utf-8" /></head></Layout></body></html>`,
+		},
+		{
+			name:   "sibling double html",
+			source: `<html><head><title>A</title></head><body><p>A</p></body></html><html><head><title>B</title></head><body><p>B</p></body></html>`,
+		},
+		{
+			name:   "head after body",
+			source: `<html><body><p>A</p></body><head><title>A</title></head></html>`,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			output := printWithOptions(t, tt.source, transform.TransformOptions{})
+			if count := strings.Count(output, "${$$renderHead($$result)}"); count > 1 {
+				t.Errorf("expected $$renderHead to be printed at most once, got %d:\n%s", count, output)
+			}
+		})
+	}
+}
+
+func TestPrintTemplateHash(t *testing.T) {
+	templateHash := func(source string, staticExtraction bool) string {
+		doc, err := astro.Parse(strings.NewReader(source))
+		if err != nil {
+			t.Error(err)
+		}
+		opts := transform.TransformOptions{StaticExtraction: staticExtraction}
+		transform.ExtractStyles(doc, &opts)
+		// The scope changes with the source, like it does without a filename
+		opts.Scope = astro.HashFromDoc(doc)
+		transform.Transform(doc, opts)
+		return PrintToJS(source, doc, len(doc.Styles), opts).TemplateHash
+	}
+	base := `<style>h1{color:red}</style><script>console.log("a")</script><h1>Hello</h1>`
+	style := `<style>h1{color:blue}</style><script>console.log("a")</script><h1>Hello</h1>`
+	script := `<style>h1{color:red}</style><script>console.log("b")</script><h1>Hello</h1>`
+	template := `<style>h1{color:red}</style><script>console.log("a")</script><h1>Hi</h1>`
+
+	for _, staticExtraction := range []bool{true, false} {
+		hash := templateHash(base, staticExtraction)
+		if templateHash(style, staticExtraction) != hash {
+			t.Errorf("expected a style change to keep the template hash (static extraction: %v)", staticExtraction)
+		}
+		if templateHash(script, staticExtraction) != hash {
+			t.Errorf("expected a script change to keep the template hash (static extraction: %v)", staticExtraction)
+		}
+		if templateHash(template, staticExtraction) == hash {
+			t.Errorf("expected a template change to change the template hash (static extraction: %v)", staticExtraction)
+		}
+	}
+}
@@ -5,6 +5,8 @@ import (
 	"strings"
 
 	"github.com/iancoleman/strcase"
+	astro "github.com/withastro/compiler/internal"
+	"github.com/withastro/compiler/internal/js_scanner"
 )
 
 func escapeText(src string) string {
@@ -53,35 +55,120 @@ func getComponentName(pathname string) string {
 	return strings.Join([]string{"$$", basename}, "")
 }
 
+// The escape helpers use strings.Replace, which returns its input as-is when
+// there is nothing to replace. Large values that need no escaping, like inline
+// data URIs, are never copied.
 func escapeExistingEscapes(src string) string {
 	return strings.Replace(src, "\\", "\\\\", -1)
 }
 
 func escapeTSXExpressions(src string) string {
-	open := regexp.MustCompile(`{`)
-	close := regexp.MustCompile(`}`)
-	return close.ReplaceAllString(open.ReplaceAllString(src, `\\{`), `\\}`)
+	return strings.Replace(strings.Replace(src, "{", `\\{`, -1), "}", `\\}`, -1)
 }
 
 func escapeInterpolation(src string) string {
-	interpolation := regexp.MustCompile(`\${`)
-	return interpolation.ReplaceAllString(src, "\\${")
+	return strings.Replace(src, "${", "\\${", -1)
 }
 
 // Escape backtick characters for Text nodes
 func escapeBackticks(src string) string {
-	backticks := regexp.MustCompile("`")
-	return backticks.ReplaceAllString(src, "\\`")
+	return strings.Replace(src, "`", "\\`", -1)
 }
 
 func escapeSingleQuote(str string) string {
-	return strings.Replace(str, "'", "\\'", -1)
+	return strings.Replace(escapeExistingEscapes(str), "'", "\\'", -1)
+}
+
+func escapeDoubleQuote(str string) string {
+	return strings.Replace(escapeExistingEscapes(str), `"`, `\"`, -1)
 }
 
 func encodeDoubleQuote(str string) string {
 	return strings.Replace(str, `"`, "&quot;", -1)
 }
 
+// Escapes the same characters as the runtime does for strings rendered as HTML
+var htmlEscaper = strings.NewReplacer("&", "&amp;", "<", "&lt;", ">", "&gt;", `"`, "&quot;", "'", "&#39;")
+
+func escapeHTML(str string) string {
+	return htmlEscaper.Replace(str)
+}
+
+// Integers that print the same in JS as they are written
+var staticIntegerExp = regexp.MustCompile(`^(0|-?[1-9][0-9]{0,14})$`)
+
+// The text a JS expression renders as when it is a string literal, an integer,
+// or a template literal without substitutions. Literals with escape sequences
+// are left for the runtime.
+func staticExpressionValue(expr string) (string, bool) {
+	expr = strings.TrimSpace(expr)
+	if staticIntegerExp.MatchString(expr) {
+		return expr, true
+	}
+	if len(expr) < 2 || expr[0] != expr[len(expr)-1] {
+		return "", false
+	}
+	value := expr[1 : len(expr)-1]
+	switch expr[0] {
+	case '"', '\'':
+		if strings.ContainsAny(value, string(expr[0])+"\\\n\r") {
+			return "", false
+		}
+		return value, true
+	case '`':
+		if strings.ContainsAny(value, "`\\") || strings.Contains(value, "${") {
+			return "", false
+		}
+		return value, true
+	}
+	return "", false
+}
+
+// The static text of an expression like `{"text"}`, if its only child is a literal
+func staticExpressionChild(n *astro.Node) (string, bool) {
+	if n.FirstChild == nil || n.FirstChild != n.LastChild || n.FirstChild.Type != astro.TextNode {
+		return "", false
+	}
+	return staticExpressionValue(n.FirstChild.Data)
+}
+
+// Matches `onclick`, `onMouseOver`, etc. Spreads are left alone since their keys are unknown.
+func isEventHandler(attr astro.Attribute) bool {
+	if attr.Type == astro.SpreadAttribute {
+		return false
+	}
+	return len(attr.Key) > 2 && strings.HasPrefix(strings.ToLower(attr.Key), "on")
+}
+
+var fragmentSpecifierExp = regexp.MustCompile(`\bFragment\s*(,\s*|$)`)
+
+// `Fragment` is always imported from the runtime, so importing it again from
+// the runtime would declare the same binding twice. Drops it from the
+// statement, or drops the whole statement when it's the only import.
+func removeRuntimeFragmentImport(statement string, internalURL string) string {
+	_, stmt := js_scanner.NextImportStatement([]byte(statement), 0)
+	if stmt.IsType || (stmt.Specifier != internalURL && stmt.Specifier != "astro/jsx-runtime") {
+		return statement
+	}
+	for _, imported := range stmt.Imports {
+		if imported.ExportName != "Fragment" || imported.LocalName != "Fragment" {
+			continue
+		}
+		if len(stmt.Imports) == 1 {
+			return ""
+		}
+		start := strings.Index(statement, "{")
+		end := strings.Index(statement, "}")
+		if start == -1 || end < start {
+			return statement
+		}
+		named := fragmentSpecifierExp.ReplaceAllString(strings.TrimSpace(statement[start+1:end]), "")
+		named = strings.TrimSuffix(strings.TrimSpace(named), ",")
+		return statement[:start+1] + " " + named + " " + statement[end:]
+	}
+	return statement
+}
+
 // Remove comment blocks from string (e.g. "/* a comment */aProp" => "aProp")
 func removeComments(input string) string {
 	var (
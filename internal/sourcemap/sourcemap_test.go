@@ -0,0 +1,72 @@
+package sourcemap
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/withastro/compiler/internal/loc"
+)
+
+func TestChunkBuilderAddSourceMapping(t *testing.T) {
+	type mapping struct {
+		// printed is appended to the output before the mapping is added
+		printed string
+		start   int
+	}
+	source := "<div>\n  <span>hi</span>\n</div>"
+	tests := []struct {
+		name     string
+		mappings []mapping
+		want     int
+	}{
+		{
+			name:     "single mapping",
+			mappings: []mapping{{"", 0}},
+			want:     1,
+		},
+		{
+			name:     "same location is deduplicated",
+			mappings: []mapping{{"", 8}, {"$$render`", 8}},
+			want:     1,
+		},
+		{
+			name:     "same location without output is deduplicated",
+			mappings: []mapping{{"", 8}, {"", 8}},
+			want:     1,
+		},
+		{
+			name:     "different locations at the same output position",
+			mappings: []mapping{{"", 0}, {"", 8}},
+			want:     2,
+		},
+		{
+			name:     "returning to an earlier location",
+			mappings: []mapping{{"", 0}, {"<div>", 8}, {"<span>", 0}},
+			want:     3,
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			b := MakeChunkBuilder(nil, GenerateLineOffsetTables(source, len(strings.Split(source, "\n"))))
+			output := []byte{}
+			for _, m := range tt.mappings {
+				output = append(output, m.printed...)
+				b.AddSourceMapping(loc.Loc{Start: m.start}, output)
+			}
+			chunk := b.GenerateChunk(output)
+
+			got := 0
+			for _, line := range strings.Split(string(chunk.Buffer), ";") {
+				for _, segment := range strings.Split(line, ",") {
+					if segment != "" {
+						got++
+					}
+				}
+			}
+			if got != tt.want {
+				t.Errorf("expected %d mappings, got %d (%q)", tt.want, got, chunk.Buffer)
+			}
+		})
+	}
+}
@@ -1,10 +1,9 @@
 package transform
 
 import (
-
-	// "strings"
-
 	"fmt"
+	"regexp"
+	"strings"
 
 	astro "github.com/withastro/compiler/internal"
 	"github.com/withastro/compiler/lib/esbuild/css_parser"
@@ -36,37 +35,152 @@ outer:
 		if n.FirstChild == nil {
 			continue
 		}
-		// Use vendored version of esbuild internals to parse AST
-		tree := css_parser.Parse(logger.Log{AddMsg: func(msg logger.Msg) {}}, logger.Source{Contents: n.FirstChild.Data}, css_parser.Options{MinifySyntax: false, MinifyWhitespace: true})
-		// esbuild's internal `css_printer` has been modified to emit Astro scoped styles
-		result := css_printer.Print(tree, css_printer.Options{MinifyWhitespace: true, Scope: opts.Scope})
-		n.FirstChild.Data = string(result.CSS)
+		result := TransformCSS(styleTextContent(n, opts), TransformCSSOptions{Scope: opts.Scope})
+		for _, err := range result.Errors {
+			fmt.Printf("%s: <style> %s\n", opts.Filename, err)
+		}
+		n.FirstChild.Data = result.Code
 	}
 
 	return didScope
 }
 
+var cssVariableExp = regexp.MustCompile(`var\(\s*(--[\w-]+)`)
+
+// Track the custom properties referenced with `var()` by scoped styles, in
+// authored order, so design-system tooling can see which tokens a component uses
+func AddCSSVariables(doc *astro.Node) {
+	// doc.Styles is collected in reverse
+	for i := len(doc.Styles) - 1; i >= 0; i-- {
+		n := doc.Styles[i]
+		if n.DataAtom != a.Style || IsGlobalStyle(n) || n.FirstChild == nil {
+			continue
+		}
+	outer:
+		for _, match := range cssVariableExp.FindAllStringSubmatch(n.FirstChild.Data, -1) {
+			for _, existing := range doc.CSSVariables {
+				if existing == match[1] {
+					continue outer
+				}
+			}
+			doc.CSSVariables = append(doc.CSSVariables, match[1])
+		}
+	}
+}
+
+// Join every text child of a <style> into its first child, so printers only
+// need to look at `n.FirstChild`. Any other children (like an expression left
+// over from error recovery) are not valid CSS, so they are dropped with a warning.
+func styleTextContent(n *astro.Node, opts TransformOptions) string {
+	var b strings.Builder
+	var text *astro.Node
+	for c := n.FirstChild; c != nil; {
+		next := c.NextSibling
+		if c.Type == astro.TextNode {
+			b.WriteString(c.Data)
+			if text == nil {
+				text = c
+			} else {
+				n.RemoveChild(c)
+			}
+		} else {
+			fmt.Printf("%s: <style> contains a non-text child which has been ignored. Styles must be plain CSS text.\n", opts.Filename)
+			n.RemoveChild(c)
+		}
+		c = next
+	}
+	if text == nil {
+		text = &astro.Node{Type: astro.TextNode}
+		n.AppendChild(text)
+	}
+	text.Data = b.String()
+	return text.Data
+}
+
+type TransformCSSOptions struct {
+	Scope    string
+	Strategy string
+}
+
+type TransformCSSResult struct {
+	Code       string
+	Errors     []string
+	ScopeClass string
+}
+
+// Scope an arbitrary CSS string, independent of any `.astro` document.
+// This is used by `<style>` scoping and exposed directly as `transformCSS`.
+func TransformCSS(source string, opts TransformCSSOptions) TransformCSSResult {
+	errors := make([]string, 0)
+	switch opts.Strategy {
+	case "", css_printer.ScopeStrategyWhere, css_printer.ScopeStrategyClass, css_printer.ScopeStrategyAttribute:
+	default:
+		errors = append(errors, fmt.Sprintf("Unknown scope strategy %q, expected %q, %q or %q. The %q strategy has been used instead.", opts.Strategy, css_printer.ScopeStrategyWhere, css_printer.ScopeStrategyClass, css_printer.ScopeStrategyAttribute, css_printer.ScopeStrategyWhere))
+		opts.Strategy = ""
+	}
+	log := logger.Log{AddMsg: func(msg logger.Msg) {
+		errors = append(errors, formatCSSMessage(msg))
+	}}
+	// Use vendored version of esbuild internals to parse AST
+	tree := css_parser.Parse(log, logger.Source{Contents: source}, css_parser.Options{MinifySyntax: false, MinifyWhitespace: true})
+	// esbuild's internal `css_printer` has been modified to emit Astro scoped styles
+	result := css_printer.Print(tree, css_printer.Options{MinifyWhitespace: true, Scope: opts.Scope, ScopeStrategy: opts.Strategy})
+	return TransformCSSResult{
+		Code:       string(result.CSS),
+		Errors:     errors,
+		ScopeClass: ScopeClass(opts.Scope, opts.Strategy),
+	}
+}
+
+// The class, or attribute for the `attribute` strategy, that elements need
+// to match the selectors printed for a scope strategy
+func ScopeClass(scope string, strategy string) string {
+	if strategy == css_printer.ScopeStrategyAttribute {
+		return "data-astro-cid-" + scope
+	}
+	return "astro-" + scope
+}
+
+func formatCSSMessage(msg logger.Msg) string {
+	if msg.Data.Location == nil {
+		return msg.Data.Text
+	}
+	return fmt.Sprintf("%d:%d: %s", msg.Data.Location.Line, msg.Data.Location.Column, msg.Data.Text)
+}
+
 func GetDefineVars(styles []*astro.Node) []string {
 	values := make([]string, 0)
 	for _, n := range styles {
 		if n.DataAtom != a.Style {
 			continue
 		}
-		if !HasAttr(n, "define:vars") {
-			continue
-		}
-		attr := GetAttr(n, "define:vars")
-		if attr != nil {
-			switch attr.Type {
-			case astro.QuotedAttribute:
-				values = append(values, fmt.Sprintf("'%s'", attr.Val))
-			case astro.TemplateLiteralAttribute:
-				values = append(values, fmt.Sprintf("`%s`", attr.Val))
-			case astro.ExpressionAttribute:
-				values = append(values, attr.Val)
-			}
+		if value := GetDefineVarsSource(n); value != "" {
+			values = append(values, value)
 		}
 	}
 
 	return values
 }
+
+// Print the `define:vars` value of a single node as a JS expression,
+// or an empty string if there isn't one
+func GetDefineVarsSource(n *astro.Node) string {
+	attr := GetAttr(n, "define:vars")
+	if attr == nil {
+		return ""
+	}
+	switch attr.Type {
+	case astro.QuotedAttribute:
+		return fmt.Sprintf("'%s'", attr.Val)
+	case astro.TemplateLiteralAttribute:
+		return fmt.Sprintf("`%s`", attr.Val)
+	case astro.ExpressionAttribute:
+		return attr.Val
+	}
+	return ""
+}
+
+// Whether a <style> opts out of scoping with `is:global` (or the legacy `global`)
+func IsGlobalStyle(n *astro.Node) bool {
+	return hasTruthyAttr(n, "is:global") || hasTruthyAttr(n, "global")
+}
@@ -6,6 +6,7 @@ import (
 
 	astro "github.com/withastro/compiler/internal"
 	"github.com/withastro/compiler/internal/test_utils"
+	a "golang.org/x/net/html/atom"
 )
 
 func TestScopeStyle(t *testing.T) {
@@ -81,6 +82,21 @@ func TestScopeStyle(t *testing.T) {
 			source: "@media screen and (min-width:640px){.class{}}",
 			want:   "@media screen and (min-width:640px){.class:where(.astro-XXXXXX){}}",
 		},
+		{
+			name:   "supports",
+			source: "@supports (display:flex){.flex{}}",
+			want:   "@supports (display:flex){.flex:where(.astro-XXXXXX){}}",
+		},
+		{
+			name:   "supports not",
+			source: "@supports not (display:grid){.fallback{}}",
+			want:   "@supports not (display:grid){.fallback:where(.astro-XXXXXX){}}",
+		},
+		{
+			name:   "supports selector()",
+			source: "@supports selector(.card:has(img)){.card{}}",
+			want:   "@supports selector(.card:has(img)){.card:where(.astro-XXXXXX){}}",
+		},
 		{
 			name:   "element + pseudo state + pseudo element",
 			source: "button:focus::before{}",
@@ -161,6 +177,31 @@ func TestScopeStyle(t *testing.T) {
 			source: ":root{}",
 			want:   ":root{}",
 		},
+		{
+			name:   ":where(:root)",
+			source: "@layer base{:where(:root){--color:red}}",
+			want:   "@layer base{:where(:root){--color:red}}",
+		},
+		{
+			name:   ":where(html)",
+			source: ":where(html){}",
+			want:   ":where(html){}",
+		},
+		{
+			name:   ":is(:root,html)",
+			source: ":is(:root,html){}",
+			want:   ":is(:root,html){}",
+		},
+		{
+			name:   ":is(.a,.b)",
+			source: ":is(.a,.b){}",
+			want:   ":is(.a,.b):where(.astro-XXXXXX){}",
+		},
+		{
+			name:   ":where(:root,.a)",
+			source: ":where(:root,.a){}",
+			want:   ":where(:root,.a):where(.astro-XXXXXX){}",
+		},
 		{
 			name:   "escaped characters",
 			source: ".class\\:class:focus{}",
@@ -288,3 +329,123 @@ func TestScopeStyle(t *testing.T) {
 		})
 	}
 }
+
+func TestTransformCSS(t *testing.T) {
+	tests := []struct {
+		name     string
+		source   string
+		strategy string
+		want     string
+		errors   int
+	}{
+		{
+			name:   "default strategy",
+			source: ".class{}",
+			want:   ".class:where(.astro-XXXXXX){}",
+		},
+		{
+			name:     "where strategy",
+			source:   "h1 .class{}",
+			strategy: "where",
+			want:     "h1:where(.astro-XXXXXX) .class:where(.astro-XXXXXX){}",
+		},
+		{
+			name:     "class strategy",
+			source:   "h1 .class{}",
+			strategy: "class",
+			want:     "h1.astro-XXXXXX .class.astro-XXXXXX{}",
+		},
+		{
+			name:     "attribute strategy",
+			source:   "h1 .class{}",
+			strategy: "attribute",
+			want:     "h1[data-astro-cid-XXXXXX] .class[data-astro-cid-XXXXXX]{}",
+		},
+		{
+			name:     "class strategy global",
+			source:   ":global(.a) .b{}",
+			strategy: "class",
+			want:     ".a .b.astro-XXXXXX{}",
+		},
+		{
+			name:     "unknown strategy",
+			source:   "h1{}",
+			strategy: "classes",
+			want:     "h1:where(.astro-XXXXXX){}",
+			errors:   1,
+		},
+		{
+			name:   "reports errors",
+			source: ".a{color:red",
+			want:   ".a:where(.astro-XXXXXX){color:red}",
+			errors: 1,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			result := TransformCSS(tt.source, TransformCSSOptions{Scope: "XXXXXX", Strategy: tt.strategy})
+			if tt.want != result.Code {
+				t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, tt.want, result.Code)
+			}
+			if tt.errors != len(result.Errors) {
+				t.Errorf("\nFAIL: %s\n  want %d errors, got: %v", tt.name, tt.errors, result.Errors)
+			}
+		})
+	}
+}
+
+func TestScopeClass(t *testing.T) {
+	for _, strategy := range []string{"", "where", "class", "attribute"} {
+		t.Run(strategy, func(t *testing.T) {
+			result := TransformCSS("h1{}", TransformCSSOptions{Scope: "XXXXXX", Strategy: strategy})
+			if !strings.Contains(result.Code, result.ScopeClass) {
+				t.Errorf("expected %s to use %s", result.Code, result.ScopeClass)
+			}
+		})
+	}
+	if got := ScopeClass("XXXXXX", "attribute"); got != "data-astro-cid-XXXXXX" {
+		t.Errorf("want: data-astro-cid-XXXXXX\ngot:  %s", got)
+	}
+}
+
+func TestScopeStyleChildren(t *testing.T) {
+	style := &astro.Node{Type: astro.ElementNode, Data: "style", DataAtom: a.Style}
+	style.AppendChild(&astro.Node{Type: astro.TextNode, Data: ".a{}"})
+	style.AppendChild(&astro.Node{Type: astro.ElementNode, Data: "astro:expression", Expression: true})
+	style.AppendChild(&astro.Node{Type: astro.TextNode, Data: ".b{}"})
+
+	ScopeStyle([]*astro.Node{style}, TransformOptions{Scope: "XXXXXX"})
+
+	want := ".a:where(.astro-XXXXXX){}.b:where(.astro-XXXXXX){}"
+	if style.FirstChild == nil || style.FirstChild != style.LastChild {
+		t.Fatalf("\nFAIL: expected a single text child")
+	}
+	if got := style.FirstChild.Data; want != got {
+		t.Errorf("\nFAIL: want: %s\n  got:  %s", want, got)
+	}
+}
+
+func TestIsGlobalStyle(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   bool
+	}{
+		{"scoped", `<style>div{}</style>`, false},
+		{"is:global", `<style is:global>div{}</style>`, true},
+		{"legacy global", `<style global>div{}</style>`, true},
+		{"other empty attribute", `<style data-foo>div{}</style>`, false},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			ExtractStyles(doc, &TransformOptions{})
+			if got := IsGlobalStyle(doc.Styles[0]); tt.want != got {
+				t.Errorf("\nFAIL: %s\n  want: %v\n  got:  %v", tt.name, tt.want, got)
+			}
+		})
+	}
+}
@@ -2,20 +2,24 @@ package transform
 
 import (
 	"fmt"
+	"strings"
 
 	astro "github.com/withastro/compiler/internal"
 )
 
 func ScopeElement(n *astro.Node, opts TransformOptions) {
-	if n.Type == astro.ElementNode {
+	if n.Type == astro.ElementNode && !n.Expression {
 		if _, noScope := NeverScopedElements[n.Data]; !noScope {
 			injectScopedClass(n, opts)
 		}
 	}
 }
 
+// Top-level elements (and elements passed to top-level components) get the
+// `define:vars` custom properties merged into their `style`. Top-level
+// components receive them as a `style` prop.
 func AddDefineVars(n *astro.Node, values []string) {
-	if n.Type == astro.ElementNode && !n.Component {
+	if n.Type == astro.ElementNode && !n.Expression {
 		if _, noScope := NeverScopedElements[n.Data]; !noScope {
 			if IsTopLevel(n) {
 				injectDefineVars(n, values)
@@ -51,7 +55,7 @@ func injectDefineVars(n *astro.Node, values []string) {
 			switch attr.Type {
 			case astro.ShorthandAttribute:
 				attr.Type = astro.ExpressionAttribute
-				attr.Val = fmt.Sprintf(`%s + %s`, attr.Key, definedVars)
+				attr.Val = fmt.Sprintf(`%s + "; " + %s`, attr.Key, definedVars)
 				n.Attr[i] = attr
 				return
 			case astro.EmptyAttribute:
@@ -59,14 +63,19 @@ func injectDefineVars(n *astro.Node, values []string) {
 				attr.Val = definedVars
 				n.Attr[i] = attr
 				return
-			case astro.QuotedAttribute, astro.TemplateLiteralAttribute:
+			case astro.QuotedAttribute:
+				attr.Type = astro.ExpressionAttribute
+				attr.Val = fmt.Sprintf("`%s; ${%s}`", escapeTemplateLiteral(strings.TrimRight(strings.TrimSpace(attr.Val), ";")), definedVars)
+				n.Attr[i] = attr
+				return
+			case astro.TemplateLiteralAttribute:
 				attr.Type = astro.ExpressionAttribute
-				attr.Val = fmt.Sprintf("`%s ${%s}`", attr.Key, definedVars)
+				attr.Val = fmt.Sprintf("`%s; ${%s}`", strings.TrimRight(strings.TrimSpace(attr.Val), ";"), definedVars)
 				n.Attr[i] = attr
 				return
 			case astro.ExpressionAttribute:
 				attr.Type = astro.ExpressionAttribute
-				attr.Val = fmt.Sprintf("(%s) + %s`", attr.Val, definedVars)
+				attr.Val = fmt.Sprintf(`(%s) + "; " + %s`, attr.Val, definedVars)
 				n.Attr[i] = attr
 				return
 			}
@@ -79,9 +88,16 @@ func injectDefineVars(n *astro.Node, values []string) {
 	})
 }
 
+// Escapes a static value for a template literal, like the printer does for text
+func escapeTemplateLiteral(src string) string {
+	src = strings.ReplaceAll(src, "\\", "\\\\")
+	src = strings.ReplaceAll(src, "${", "\\${")
+	return strings.ReplaceAll(src, "`", "\\`")
+}
+
 func injectScopedClass(n *astro.Node, opts TransformOptions) {
 	hasSpreadAttr := false
-	scopedClass := fmt.Sprintf(`astro-%s`, opts.Scope)
+	scopedClass := ScopeClass(opts.Scope, "")
 	for i, attr := range n.Attr {
 		if !hasSpreadAttr && attr.Type == astro.SpreadAttribute {
 			// We only handle this special case on built-in elements
@@ -93,7 +109,7 @@ func injectScopedClass(n *astro.Node, opts TransformOptions) {
 			switch attr.Type {
 			case astro.ShorthandAttribute:
 				if n.Component {
-					attr.Val = fmt.Sprintf(`%s + " %s"`, attr.Key, scopedClass)
+					attr.Val = fmt.Sprintf(`((%s) ?? "") + " %s"`, attr.Key, scopedClass)
 					attr.Type = astro.ExpressionAttribute
 					n.Attr[i] = attr
 					return
@@ -110,8 +126,8 @@ func injectScopedClass(n *astro.Node, opts TransformOptions) {
 				n.Attr[i] = attr
 				return
 			case astro.ExpressionAttribute:
-				// as an expression
-				attr.Val = fmt.Sprintf(`(%s) + " %s"`, attr.Val, scopedClass)
+				// as an expression, without printing `undefined` or `null` for a missing class
+				attr.Val = fmt.Sprintf(`((%s) ?? "") + " %s"`, attr.Val, scopedClass)
 				n.Attr[i] = attr
 				return
 			}
@@ -122,12 +138,12 @@ func injectScopedClass(n *astro.Node, opts TransformOptions) {
 			case astro.EmptyAttribute:
 				// instead of an empty string
 				attr.Type = astro.QuotedAttribute
-				attr.Val = "astro-" + opts.Scope
+				attr.Val = scopedClass
 				n.Attr[i] = attr
 				return
 			case astro.QuotedAttribute, astro.TemplateLiteralAttribute:
 				// as a plain string
-				attr.Val = attr.Val + " astro-" + opts.Scope
+				attr.Val = attr.Val + " " + scopedClass
 				n.Attr[i] = attr
 				return
 			case astro.ExpressionAttribute:
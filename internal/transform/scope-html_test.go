@@ -32,17 +32,27 @@ func TestScopeHTML(t *testing.T) {
 		{
 			name:   "expression string",
 			source: `<div class={"test"} />`,
-			want:   `<div class={("test") + " astro-XXXXXX"}></div>`,
+			want:   `<div class={(("test") ?? "") + " astro-XXXXXX"}></div>`,
 		},
 		{
 			name:   "expression function",
 			source: `<div class={clsx({ [test]: true })} />`,
-			want:   `<div class={(clsx({ [test]: true })) + " astro-XXXXXX"}></div>`,
+			want:   `<div class={((clsx({ [test]: true })) ?? "") + " astro-XXXXXX"}></div>`,
 		},
 		{
 			name:   "expression dynamic",
 			source: "<div class={condition ? 'a' : 'b'} />",
-			want:   `<div class={(condition ? 'a' : 'b') + " astro-XXXXXX"}></div>`,
+			want:   `<div class={((condition ? 'a' : 'b') ?? "") + " astro-XXXXXX"}></div>`,
+		},
+		{
+			name:   "expression nullish",
+			source: "<div class={dynamicClass} />",
+			want:   `<div class={((dynamicClass) ?? "") + " astro-XXXXXX"}></div>`,
+		},
+		{
+			name:   "expression with a lower precedence operator",
+			source: "<div class={a || b} />",
+			want:   `<div class={((a || b) ?? "") + " astro-XXXXXX"}></div>`,
 		},
 		{
 			name:   "empty",
@@ -54,6 +64,16 @@ func TestScopeHTML(t *testing.T) {
 			source: "<div class=`${value}` />",
 			want:   "<div class=`${value} astro-XXXXXX`></div>",
 		},
+		{
+			name:   "spread",
+			source: "<div {...props} />",
+			want:   `<div {...props}></div>`,
+		},
+		{
+			name:   "spread with class",
+			source: `<div {...props} class="test" />`,
+			want:   `<div {...props} class="test astro-XXXXXX"></div>`,
+		},
 		{
 			name:   "component className not scoped",
 			source: `<Component className="test" />`,
@@ -62,18 +82,38 @@ func TestScopeHTML(t *testing.T) {
 		{
 			name:   "component className expression",
 			source: `<Component className={"test"} />`,
-			want:   `<Component className={("test") + " astro-XXXXXX"}></Component>`,
+			want:   `<Component className={(("test") ?? "") + " astro-XXXXXX"}></Component>`,
 		},
 		{
 			name:   "component className shorthand",
 			source: "<Component {className} />",
-			want:   `<Component className={className + " astro-XXXXXX"}></Component>`,
+			want:   `<Component className={((className) ?? "") + " astro-XXXXXX"}></Component>`,
+		},
+		{
+			name:   "component class",
+			source: `<Component class="foo" />`,
+			want:   `<Component class="foo astro-XXXXXX"></Component>`,
+		},
+		{
+			name:   "component class expression",
+			source: `<Component class={expr} />`,
+			want:   `<Component class={((expr) ?? "") + " astro-XXXXXX"}></Component>`,
+		},
+		{
+			name:   "component without class",
+			source: `<Component />`,
+			want:   `<Component class="astro-XXXXXX"></Component>`,
 		},
 		{
 			name:   "element class:list",
 			source: "<div class:list={{ a: true }} />",
 			want:   `<div class:list={[({ a: true }), "astro-XXXXXX"]}></div>`,
 		},
+		{
+			name:   "element class:list mixed array",
+			source: "<div class:list={[\"foo\", isDark && \"dark\"]} />",
+			want:   `<div class:list={[(["foo", isDark && "dark"]), "astro-XXXXXX"]}></div>`,
+		},
 		{
 			name:   "element class:list string",
 			source: "<div class:list=\"weird but ok\" />",
@@ -101,3 +141,79 @@ func TestScopeHTML(t *testing.T) {
 		})
 	}
 }
+
+func TestAddDefineVars(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "element",
+			source: "<div />",
+			want:   `<div style={$$definedVars}></div>`,
+		},
+		{
+			name:   "element with static style",
+			source: `<div style="color: red;" />`,
+			want:   "<div style={`color: red; ${$$definedVars}`}></div>",
+		},
+		{
+			name:   "element with escaped style",
+			source: `<div style='content: "\201C"' />`,
+			want:   "<div style={`content: \"\\\\201C\"; ${$$definedVars}`}></div>",
+		},
+		{
+			name:   "element with backticks in style",
+			source: "<div style=\"--a: '`${x}'\" />",
+			want:   "<div style={`--a: '\\`\\${x}'; ${$$definedVars}`}></div>",
+		},
+		{
+			name:   "element with template literal style",
+			source: "<div style=`color: ${color}` />",
+			want:   "<div style={`color: ${color}; ${$$definedVars}`}></div>",
+		},
+		{
+			name:   "element with dynamic style",
+			source: `<div style={styles} />`,
+			want:   `<div style={(styles) + "; " + $$definedVars}></div>`,
+		},
+		{
+			name:   "element with shorthand style",
+			source: `<div {style} />`,
+			want:   `<div style={style + "; " + $$definedVars}></div>`,
+		},
+		{
+			name:   "nested element",
+			source: "<div><span /></div>",
+			want:   `<div style={$$definedVars}><span></span></div>`,
+		},
+		{
+			name:   "component root",
+			source: "<Component />",
+			want:   `<Component style={$$definedVars}></Component>`,
+		},
+		{
+			name:   "component root with children",
+			source: `<Component style="color: red"><div /></Component>`,
+			want:   "<Component style={`color: red; ${$$definedVars}`}><div style={$$definedVars}></div></Component>",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			nodes, err := astro.ParseFragment(strings.NewReader(tt.source), &astro.Node{Type: astro.ElementNode, DataAtom: atom.Body, Data: atom.Body.String()})
+			if err != nil {
+				t.Error(err)
+			}
+			walk(nodes[0], func(n *astro.Node) {
+				AddDefineVars(n, []string{"{ color }"})
+			})
+			var b strings.Builder
+			astro.PrintToSource(&b, nodes[0])
+			got := b.String()
+			if tt.want != got {
+				t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, tt.want, got)
+			}
+		})
+	}
+}
@@ -13,24 +13,58 @@ import (
 )
 
 type TransformOptions struct {
-	Scope            string
-	Filename         string
-	Pathname         string
-	InternalURL      string
-	SourceMap        string
-	Site             string
-	ProjectRoot      string
-	Compact          bool
-	PreprocessStyle  interface{}
-	StaticExtraction bool
+	Scope                 string
+	Filename              string
+	Pathname              string
+	InternalURL           string
+	SourceMap             string
+	Site                  string
+	ProjectRoot           string
+	Compact               bool
+	PreprocessStyle       interface{}
+	StaticExtraction      bool
+	HMR                   bool
+	StripEventHandlers    bool
+	PreserveSlotComments  bool
+	Define                map[string]string
+	InlineScriptThreshold int
+	OutputExtension       string
+	ValidateHTMLNesting   bool
+	DisableRuntimeImports bool
+	JSXImportSource       string
+	NormalizedFilename    string
+	StylePreprocessorMap  map[string]string
+	EmitCSSInline         bool
+	ScopedSlotNames       []string
 }
 
 func Transform(doc *astro.Node, opts TransformOptions) *astro.Node {
 	shouldScope := len(doc.Styles) > 0 && ScopeStyle(doc.Styles, opts)
+	AddCSSVariables(doc)
 	definedVars := GetDefineVars(doc.Styles)
 	walk(doc, func(n *astro.Node) {
 		ExtractScript(doc, n, &opts)
 		AddComponentProps(doc, n, &opts)
+		AddAstroAPIUsage(doc, n)
+		AddMetaTag(doc, n)
+		if warning := TransitionConflictWarning(n); warning != "" {
+			fmt.Printf("%s: %s\n", opts.Filename, warning)
+		}
+		if warning := ExpressionChildWarning(n); warning != "" {
+			fmt.Printf("%s: %s\n", opts.Filename, warning)
+		}
+		if opts.ValidateHTMLNesting {
+			if warning := HTMLNestingWarning(n); warning != "" {
+				fmt.Printf("%s: %s\n", opts.Filename, warning)
+			}
+		}
+		if len(opts.Define) > 0 {
+			ReplaceDefines(n, opts.Define)
+		}
+		if n.Type == astro.ElementNode && n.DataAtom == a.Head && !IsImplictNode(n) {
+			// Includes <head> elements in conditional branches, which may or may not render
+			doc.ContainsHead = true
+		}
 		if shouldScope {
 			ScopeElement(n, opts)
 		}
@@ -67,18 +101,36 @@ func Transform(doc *astro.Node, opts TransformOptions) *astro.Node {
 	return doc
 }
 
-func ExtractStyles(doc *astro.Node) {
+// NormalizeFilename returns filename relative to root with a leading slash, like
+// `/src/pages/index.astro`, the same way the Vite plugin does. Backslashes are
+// treated as separators and drive letters are matched case-insensitively.
+// Files outside of root are returned with only their separators normalized.
+func NormalizeFilename(filename string, root string) string {
+	filename = lowerDriveLetter(strings.ReplaceAll(filename, "\\", "/"))
+	root = lowerDriveLetter(strings.TrimRight(strings.ReplaceAll(root, "\\", "/"), "/"))
+	if root != "" && strings.HasPrefix(filename, root+"/") {
+		return filename[len(root):]
+	}
+	return filename
+}
+
+func lowerDriveLetter(path string) string {
+	if len(path) >= 2 && path[1] == ':' && path[0] >= 'A' && path[0] <= 'Z' {
+		return strings.ToLower(path[:1]) + path[1:]
+	}
+	return path
+}
+
+func ExtractStyles(doc *astro.Node, opts *TransformOptions) {
 	walk(doc, func(n *astro.Node) {
-		if n.Type == astro.ElementNode && n.DataAtom == a.Style {
-			if HasSetDirective(n) || HasInlineDirective(n) {
-				return
-			}
-			// Ignore styles in svg/noscript/etc
-			if !IsHoistable(n) {
-				return
+		if ShouldExtractStyle(n) {
+			if warning := SlottedStyleWarning(n); warning != "" {
+				fmt.Printf("%s: %s\n", opts.Filename, warning)
 			}
 			// prepend node to maintain authored order
 			doc.Styles = append([]*astro.Node{n}, doc.Styles...)
+		} else if warning := DynamicStyleWarning(n); warning != "" {
+			fmt.Printf("%s: %s\n", opts.Filename, warning)
 		}
 	})
 	// Important! Remove styles from original location *after* walking the doc
@@ -87,6 +139,33 @@ func ExtractStyles(doc *astro.Node) {
 	}
 }
 
+// Styles passed as component children are still extracted and scoped to this file
+func SlottedStyleWarning(n *astro.Node) string {
+	if !ShouldExtractStyle(n) {
+		return ""
+	}
+	if component := ClosestComponent(n); component != nil {
+		return fmt.Sprintf("<style> inside of <%s> has been hoisted out of the component's slot and will be scoped to this file. Add `is:inline` to keep it in the slot.", component.Data)
+	}
+	return ""
+}
+
+// A <style> with a dynamic attribute can't be extracted, so it's rendered as-is
+func DynamicStyleWarning(n *astro.Node) string {
+	if n.Type != astro.ElementNode || n.DataAtom != a.Style || HasInlineDirective(n) {
+		return ""
+	}
+	attr := GetDynamicStyleAttr(n)
+	if attr == nil {
+		return ""
+	}
+	name := attr.Key
+	if attr.Type == astro.SpreadAttribute {
+		name = fmt.Sprintf("{...%s}", strings.TrimSpace(attr.Key))
+	}
+	return fmt.Sprintf("<style> has the dynamic attribute `%s` and will be rendered inline without scoping or preprocessing. Add `is:inline` to silence this warning.", name)
+}
+
 func NormalizeSetDirectives(doc *astro.Node) {
 	var nodes []*astro.Node
 	var directives []*astro.Attribute
@@ -250,12 +329,16 @@ func ExtractScript(doc *astro.Node, n *astro.Node, opts *TransformOptions) {
 		if !IsHoistable(n) {
 			return
 		}
+		// Import maps and shimmed modules must run where they are authored
+		if scriptType := GetAttr(n, "type"); scriptType != nil && (scriptType.Val == "importmap" || scriptType.Val == "module/shim") {
+			return
+		}
 
 		// if <script>, hoist to the document root
 		// If also using define:vars, that overrides the hoist tag.
-		if (hasTruthyAttr(n, "hoist")) ||
-			len(n.Attr) == 0 || (len(n.Attr) == 1 && n.Attr[0].Key == "src") {
+		if hasHoistableScriptAttrs(n) {
 			shouldAdd := true
+			src := GetAttr(n, "src")
 			for _, attr := range n.Attr {
 				if attr.Key == "hoist" {
 					fmt.Printf("%s: <script hoist> is no longer needed. You may remove the `hoist` attribute.\n", opts.Filename)
@@ -271,8 +354,20 @@ func ExtractScript(doc *astro.Node, n *astro.Node, opts *TransformOptions) {
 				}
 			}
 
+			// Scripts smaller than the threshold aren't worth an extra request
+			if shouldAdd && opts.InlineScriptThreshold > 0 && src == nil && n.FirstChild != nil {
+				shouldAdd = len(strings.TrimSpace(n.FirstChild.Data)) >= opts.InlineScriptThreshold
+				// Hoisted scripts run as modules, so the inline script needs to as well
+				if !shouldAdd && !HasAttr(n, "type") {
+					n.Attr = append(n.Attr, astro.Attribute{Type: astro.QuotedAttribute, Key: "type", Val: "module"})
+				}
+			}
+
 			// prepend node to maintain authored order
 			if shouldAdd {
+				if component := ClosestComponent(n); component != nil {
+					fmt.Printf("%s: <script> inside of <%s> has been hoisted out of the component's slot. Add `is:inline` to keep it in the slot.\n", opts.Filename, component.Data)
+				}
 				doc.Scripts = append([]*astro.Node{n}, doc.Scripts...)
 			}
 		} else {
@@ -286,6 +381,12 @@ func ExtractScript(doc *astro.Node, n *astro.Node, opts *TransformOptions) {
 }
 
 func AddComponentProps(doc *astro.Node, n *astro.Node, opts *TransformOptions) {
+	if n.Type == astro.ElementNode && n.CustomElement {
+		addCustomElement(doc, n.Data)
+	}
+	if n.Type == astro.ElementNode && n.Component {
+		addResolvedImport(doc, n, opts)
+	}
 	if n.Type == astro.ElementNode && (n.Component || n.CustomElement) {
 		for _, attr := range n.Attr {
 			id := n.Data
@@ -308,10 +409,11 @@ func AddComponentProps(doc *astro.Node, n *astro.Node, opts *TransformOptions) {
 
 				if attr.Key == "client:only" {
 					doc.ClientOnlyComponentNodes = append([]*astro.Node{n}, doc.ClientOnlyComponentNodes...)
+					addClientOnlyFramework(doc, attr)
 
 					match := matchNodeToImportStatement(doc, n)
 					if match != nil {
-						doc.ClientOnlyComponents = append(doc.ClientOnlyComponents, &astro.HydratedComponentMetadata{
+						doc.ClientOnlyComponents = appendHydratedComponent(doc.ClientOnlyComponents, &astro.HydratedComponentMetadata{
 							ExportName:   match.ExportName,
 							Specifier:    match.Specifier,
 							ResolvedPath: resolveIdForMatch(match, opts),
@@ -320,8 +422,10 @@ func AddComponentProps(doc *astro.Node, n *astro.Node, opts *TransformOptions) {
 
 					break
 				}
-				// prepend node to maintain authored order
-				doc.HydratedComponentNodes = append([]*astro.Node{n}, doc.HydratedComponentNodes...)
+				// prepend node to maintain authored order, once per component
+				if !hasHydratedComponentNode(doc, n) {
+					doc.HydratedComponentNodes = append([]*astro.Node{n}, doc.HydratedComponentNodes...)
+				}
 				pathAttr := astro.Attribute{
 					Key:  "client:component-path",
 					Val:  fmt.Sprintf("$$metadata.getPath(%s)", id),
@@ -338,7 +442,7 @@ func AddComponentProps(doc *astro.Node, n *astro.Node, opts *TransformOptions) {
 
 				match := matchNodeToImportStatement(doc, n)
 				if match != nil {
-					doc.HydratedComponents = append(doc.HydratedComponents, &astro.HydratedComponentMetadata{
+					doc.HydratedComponents = appendHydratedComponent(doc.HydratedComponents, &astro.HydratedComponentMetadata{
 						ExportName:   match.ExportName,
 						Specifier:    match.Specifier,
 						ResolvedPath: resolveIdForMatch(match, opts),
@@ -351,6 +455,26 @@ func AddComponentProps(doc *astro.Node, n *astro.Node, opts *TransformOptions) {
 	}
 }
 
+// Whether the same component, by tag name, has already been hydrated elsewhere in the document
+func hasHydratedComponentNode(doc *astro.Node, n *astro.Node) bool {
+	for _, node := range doc.HydratedComponentNodes {
+		if node.Data == n.Data && node.CustomElement == n.CustomElement {
+			return true
+		}
+	}
+	return false
+}
+
+// Components used more than once are only listed once
+func appendHydratedComponent(components []*astro.HydratedComponentMetadata, component *astro.HydratedComponentMetadata) []*astro.HydratedComponentMetadata {
+	for _, c := range components {
+		if *c == *component {
+			return components
+		}
+	}
+	return append(components, component)
+}
+
 type ImportMatch struct {
 	ExportName string
 	Specifier  string
@@ -415,6 +539,408 @@ func eachImportStatement(doc *astro.Node, cb func(stmt js_scanner.ImportStatemen
 	}
 }
 
+// Track SSR-only `Astro` APIs used in the frontmatter, expressions and
+// expression attributes, so adapters can detect pages which need SSR
+func AddAstroAPIUsage(doc *astro.Node, n *astro.Node) {
+	for _, source := range scriptSources(n) {
+	outer:
+		for _, api := range js_scanner.AstroAPIUsage([]byte(source)) {
+			for _, existing := range doc.AstroAPIUsage {
+				if existing == api {
+					continue outer
+				}
+			}
+			doc.AstroAPIUsage = append(doc.AstroAPIUsage, api)
+		}
+	}
+}
+
+// The JavaScript of a node: frontmatter or expression text, or the values
+// of expression attributes
+func scriptSources(n *astro.Node) []string {
+	sources := make([]string, 0)
+	switch n.Type {
+	case astro.TextNode:
+		if n.Parent != nil && (n.Parent.Type == astro.FrontmatterNode || n.Parent.Expression) {
+			sources = append(sources, n.Data)
+		}
+	case astro.ElementNode:
+		for _, attr := range n.Attr {
+			switch attr.Type {
+			case astro.ExpressionAttribute, astro.SpreadAttribute, astro.TemplateLiteralAttribute:
+				sources = append(sources, attr.Val)
+			}
+		}
+	}
+	return sources
+}
+
+var metaTagTypes = []string{"charset", "name", "property", "http-equiv"}
+
+// Track static <meta> tags so the runtime can deduplicate them in <head>.
+// Tags with a dynamic key or content are skipped.
+func AddMetaTag(doc *astro.Node, n *astro.Node) {
+	if n.Type != astro.ElementNode || n.DataAtom != a.Meta {
+		return
+	}
+	for _, tagType := range metaTagTypes {
+		attr := GetAttr(n, tagType)
+		if attr == nil {
+			continue
+		}
+		if attr.Type != astro.QuotedAttribute {
+			return
+		}
+		content := ""
+		if contentAttr := GetAttr(n, "content"); contentAttr != nil {
+			if contentAttr.Type != astro.QuotedAttribute {
+				return
+			}
+			content = contentAttr.Val
+		}
+		doc.MetaTags = append(doc.MetaTags, &astro.MetaTagMetadata{
+			TagType: tagType,
+			Key:     attr.Val,
+			Content: content,
+		})
+		return
+	}
+}
+
+// Replace `define` keys (like `import.meta.env.SSR`) in the frontmatter,
+// expressions and expression attributes with their values
+func ReplaceDefines(n *astro.Node, define map[string]string) {
+	switch n.Type {
+	case astro.TextNode:
+		if n.Parent != nil && (n.Parent.Type == astro.FrontmatterNode || n.Parent.Expression) {
+			n.Data = string(js_scanner.ReplaceDefines([]byte(n.Data), define))
+		}
+	case astro.ElementNode:
+		for i, attr := range n.Attr {
+			switch attr.Type {
+			case astro.ExpressionAttribute:
+				n.Attr[i].Val = string(js_scanner.ReplaceDefines([]byte(attr.Val), define))
+			case astro.SpreadAttribute:
+				// The spread expression is stored as the key
+				n.Attr[i].Key = string(js_scanner.ReplaceDefines([]byte(attr.Key), define))
+			case astro.TemplateLiteralAttribute:
+				// Lex the value as a template literal so only `${}` placeholders are replaced
+				replaced := js_scanner.ReplaceDefines([]byte("`"+attr.Val+"`"), define)
+				n.Attr[i].Val = string(replaced[1 : len(replaced)-1])
+			}
+		}
+	}
+}
+
+// `Astro.fetchContent` has been removed, so the component would fail as soon
+// as it's rendered. This is reported as an error rather than a warning.
+func FetchContentError(n *astro.Node) string {
+	for _, source := range scriptSources(n) {
+		if js_scanner.UsesFetchContent([]byte(source)) {
+			return "Astro.fetchContent() has been removed. Use Astro.glob() or content collections instead."
+		}
+	}
+	return ""
+}
+
+// The first FetchContentError in the document, if any. Transform doesn't
+// fail on this itself, callers decide how to report it.
+func FindFetchContentError(doc *astro.Node) string {
+	err := ""
+	walk(doc, func(n *astro.Node) {
+		if err == "" {
+			err = FetchContentError(n)
+		}
+	})
+	return err
+}
+
+// An <html>, <head> or <body> which conflicts with another element. The
+// parser merges a second <html> or <body> into the first and drops a stray
+// <head>, but one nested in a component or expression is kept. That's usually
+// a copy-paste accident, and only the first <head> renders the head.
+type DuplicateDocumentElement struct {
+	Node  *astro.Node
+	Other *astro.Node // The element Node conflicts with
+}
+
+func (d DuplicateDocumentElement) Message(source string) string {
+	position, other := nodePosition(source, d.Node), nodePosition(source, d.Other)
+	switch {
+	case d.Other.DataAtom == d.Node.DataAtom && isAncestor(d.Other, d.Node):
+		return fmt.Sprintf("<%s> at %s is nested inside of the <%s> at %s. A document should only have one <%s>.", d.Node.Data, position, d.Other.Data, other, d.Node.Data)
+	case d.Other.DataAtom == a.Head:
+		return fmt.Sprintf("<head> at %s comes after the <head> at %s. Only the first <head> renders head content, so a document should only have one <head>.", position, other)
+	case isAncestor(d.Other, d.Node):
+		return fmt.Sprintf("<head> at %s is inside of <%s> at %s. Head content is rendered where the first <head> closes, so <head> should be a direct child of <html>.", position, d.Other.Data, other)
+	default:
+		return fmt.Sprintf("<head> at %s comes after the <body> at %s. Head content is rendered where the first <head> closes, so it should come before <body>.", position, other)
+	}
+}
+
+// Finds every <html> or <body> nested in another one, and every <head>
+// which is inside of <body> or a component, follows another <head> or comes
+// after <body>, in the order they appear
+func FindDuplicateDocumentElements(doc *astro.Node) []DuplicateDocumentElement {
+	duplicates := make([]DuplicateDocumentElement, 0)
+	var firstHead *astro.Node
+	walk(doc, func(n *astro.Node) {
+		if n.Type != astro.ElementNode || (n.DataAtom != a.Html && n.DataAtom != a.Head && n.DataAtom != a.Body) || IsImplictNode(n) {
+			return
+		}
+		for parent := n.Parent; parent != nil; parent = parent.Parent {
+			if parent.Type != astro.ElementNode {
+				continue
+			}
+			if parent.DataAtom == n.DataAtom || (n.DataAtom == a.Head && (parent.DataAtom == a.Body || parent.Component)) {
+				duplicates = append(duplicates, DuplicateDocumentElement{Node: n, Other: parent})
+				return
+			}
+		}
+		if n.DataAtom != a.Head {
+			return
+		}
+		if firstHead != nil {
+			duplicates = append(duplicates, DuplicateDocumentElement{Node: n, Other: firstHead})
+			return
+		}
+		firstHead = n
+		for sibling := n.PrevSibling; sibling != nil; sibling = sibling.PrevSibling {
+			if sibling.Type == astro.ElementNode && sibling.DataAtom == a.Body {
+				duplicates = append(duplicates, DuplicateDocumentElement{Node: n, Other: sibling})
+				return
+			}
+		}
+	})
+	return duplicates
+}
+
+func isAncestor(ancestor *astro.Node, n *astro.Node) bool {
+	for parent := n.Parent; parent != nil; parent = parent.Parent {
+		if parent == ancestor {
+			return true
+		}
+	}
+	return false
+}
+
+// `transition:persist` and `transition:name` are both passed through as-is.
+// When both have different static values, the persist value identifies the
+// element across navigations and the name only applies to the animation,
+// which is easy to get wrong.
+func TransitionConflictWarning(n *astro.Node) string {
+	if n.Type != astro.ElementNode {
+		return ""
+	}
+	persist := GetAttr(n, "transition:persist")
+	name := GetAttr(n, "transition:name")
+	if persist == nil || name == nil {
+		return ""
+	}
+	if persist.Type != astro.QuotedAttribute || name.Type != astro.QuotedAttribute || persist.Val == "" || persist.Val == name.Val {
+		return ""
+	}
+	return fmt.Sprintf("<%s> has both transition:persist=\"%s\" and transition:name=\"%s\". The element will be persisted as \"%s\", transition:name only names its animation.", n.Data, persist.Val, name.Val, persist.Val)
+}
+
+// A <style> or <script> that is the direct result of an expression can't be
+// extracted or hoisted, so it's rendered as-is which is easy to miss.
+func ExpressionChildWarning(n *astro.Node) string {
+	if n.Type != astro.ElementNode || (n.DataAtom != a.Style && n.DataAtom != a.Script) {
+		return ""
+	}
+	// Other scripts are rendered inline wherever they are
+	if n.DataAtom == a.Script && !hasHoistableScriptAttrs(n) {
+		return ""
+	}
+	if n.Parent == nil || !n.Parent.Expression || HasInlineDirective(n) || HasSetDirective(n) {
+		return ""
+	}
+	return fmt.Sprintf("<%s> inside of an expression can't be hoisted and will be rendered inline without processing. Wrap it in an element, or add `is:inline` to silence this warning.", n.Data)
+}
+
+// Elements that may only be direct children of the listed parents. Only
+// rules the parser leaves intact are checked, everything else is either
+// fixed up while parsing or too noisy to be worth a warning.
+var allowedParents = map[a.Atom][]a.Atom{
+	a.Li:       {a.Ul, a.Ol, a.Menu},
+	a.Option:   {a.Select, a.Datalist, a.Optgroup},
+	a.Optgroup: {a.Select},
+	a.Dt:       {a.Dl, a.Div},
+	a.Dd:       {a.Dl, a.Div},
+}
+
+// Elements whose element children are restricted to the listed tags
+var allowedChildren = map[a.Atom][]a.Atom{
+	a.Ul:     {a.Li, a.Script, a.Template},
+	a.Ol:     {a.Li, a.Script, a.Template},
+	a.Select: {a.Option, a.Optgroup, a.Hr, a.Script, a.Template},
+	a.Dl:     {a.Dt, a.Dd, a.Div, a.Script, a.Template},
+}
+
+// Components, custom elements, slots and expressions can render anything,
+// so nesting is only checked between two plain HTML elements
+func isPlainElement(n *astro.Node) bool {
+	return n != nil && n.Type == astro.ElementNode && n.DataAtom != 0 && n.DataAtom != a.Slot && !n.Component && !n.CustomElement && !n.Fragment && !n.Expression
+}
+
+func containsAtom(list []a.Atom, atom a.Atom) bool {
+	for _, item := range list {
+		if item == atom {
+			return true
+		}
+	}
+	return false
+}
+
+// A component which is used both with and without a client: directive in the
+// same file is rendered on the server and also bundled for the client.
+type MixedHydration struct {
+	Name       string
+	Static     *astro.Node // The first usage without a client: directive
+	Hydrated   *astro.Node // The first usage with a client: directive
+	ClientOnly bool        // Whether Hydrated uses client:only
+}
+
+// Whether this is an error rather than a warning. client:only imports are
+// removed from the server build, so the static usage fails to render.
+func (m MixedHydration) IsError() bool {
+	return m.ClientOnly
+}
+
+func (m MixedHydration) Message(source string) string {
+	if m.ClientOnly {
+		return fmt.Sprintf("<%s> is used with client:only at %s and rendered on the server at %s. The import of a client:only component is removed from the server build, so the server-rendered <%s> will fail.", m.Name, nodePosition(source, m.Hydrated), nodePosition(source, m.Static), m.Name)
+	}
+	return fmt.Sprintf("<%s> is hydrated at %s and rendered without a client: directive at %s. It will be rendered on the server and also bundled for the client, which is usually a mistake.", m.Name, nodePosition(source, m.Hydrated), nodePosition(source, m.Static))
+}
+
+// Finds every component which is used both with and without hydration, in
+// the order they first appear. client:only usages are preferred over other
+// directives since they are reported as errors.
+func FindMixedHydration(doc *astro.Node) []MixedHydration {
+	names := make([]string, 0)
+	static := make(map[string]*astro.Node)
+	hydrated := make(map[string]*astro.Node)
+	clientOnly := make(map[string]*astro.Node)
+	walk(doc, func(n *astro.Node) {
+		if n.Type != astro.ElementNode || !n.Component {
+			return
+		}
+		if static[n.Data] == nil && hydrated[n.Data] == nil && clientOnly[n.Data] == nil {
+			names = append(names, n.Data)
+		}
+		isHydrated := false
+		for _, attr := range n.Attr {
+			if strings.HasPrefix(attr.Key, "client:") {
+				isHydrated = true
+				break
+			}
+		}
+		switch {
+		case HasAttr(n, "client:only"):
+			if clientOnly[n.Data] == nil {
+				clientOnly[n.Data] = n
+			}
+		case isHydrated:
+			if hydrated[n.Data] == nil {
+				hydrated[n.Data] = n
+			}
+		default:
+			if static[n.Data] == nil {
+				static[n.Data] = n
+			}
+		}
+	})
+
+	mixed := make([]MixedHydration, 0)
+	for _, name := range names {
+		if static[name] == nil {
+			continue
+		}
+		if clientOnly[name] != nil {
+			mixed = append(mixed, MixedHydration{Name: name, Static: static[name], Hydrated: clientOnly[name], ClientOnly: true})
+		} else if hydrated[name] != nil {
+			mixed = append(mixed, MixedHydration{Name: name, Static: static[name], Hydrated: hydrated[name]})
+		}
+	}
+	return mixed
+}
+
+// The 1-based line:column of a node in source
+func nodePosition(source string, n *astro.Node) string {
+	if len(n.Loc) == 0 || n.Loc[0].Start > len(source) {
+		return "an unknown position"
+	}
+	before := source[:n.Loc[0].Start]
+	line := strings.Count(before, "\n") + 1
+	column := n.Loc[0].Start - strings.LastIndex(before, "\n")
+	return fmt.Sprintf("%d:%d", line, column)
+}
+
+// Flags a small set of clearly invalid parent/child relationships, like a
+// <div> directly inside of a <ul>
+func HTMLNestingWarning(n *astro.Node) string {
+	if !isPlainElement(n) || !isPlainElement(n.Parent) || IsImplictNode(n.Parent) {
+		return ""
+	}
+	parents, hasParentRule := allowedParents[n.DataAtom]
+	children, hasChildRule := allowedChildren[n.Parent.DataAtom]
+	if (hasParentRule && !containsAtom(parents, n.Parent.DataAtom)) || (hasChildRule && !containsAtom(children, n.DataAtom)) {
+		return fmt.Sprintf("<%s> is not a valid child of <%s>.", n.Data, n.Parent.Data)
+	}
+	return ""
+}
+
+// Map the import specifier of every component used in the template to its
+// resolved path, for integrations building a module graph
+func addResolvedImport(doc *astro.Node, n *astro.Node, opts *TransformOptions) {
+	match := matchNodeToImportStatement(doc, n)
+	if match == nil {
+		return
+	}
+	resolved := resolveIdForMatch(match, opts)
+	if resolved == "" {
+		return
+	}
+	if doc.ResolvedImports == nil {
+		doc.ResolvedImports = make(map[string]string)
+	}
+	doc.ResolvedImports[match.Specifier] = resolved
+}
+
+// Track every custom element tag name used, in authored order, so
+// integrations can register them
+func addCustomElement(doc *astro.Node, tag string) {
+	for _, existing := range doc.CustomElements {
+		if existing == tag {
+			return
+		}
+	}
+	doc.CustomElements = append(doc.CustomElements, tag)
+}
+
+// Track the framework hint of every client:only directive, in authored order, so
+// build tools know which renderers to bundle. Dynamic hints are recorded as "unknown"
+func addClientOnlyFramework(doc *astro.Node, attr astro.Attribute) {
+	framework := ""
+	switch attr.Type {
+	case astro.QuotedAttribute:
+		framework = attr.Val
+	case astro.ExpressionAttribute, astro.TemplateLiteralAttribute:
+		framework = "unknown"
+	}
+	if framework == "" {
+		return
+	}
+	for _, existing := range doc.ClientOnlyFrameworks {
+		if existing == framework {
+			return
+		}
+	}
+	doc.ClientOnlyFrameworks = append(doc.ClientOnlyFrameworks, framework)
+}
+
 func walk(doc *astro.Node, cb func(*astro.Node)) {
 	var f func(*astro.Node)
 	f = func(n *astro.Node) {
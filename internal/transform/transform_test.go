@@ -5,6 +5,8 @@ import (
 	"testing"
 
 	astro "github.com/withastro/compiler/internal"
+	"github.com/withastro/compiler/internal/loc"
+	a "golang.org/x/net/html/atom"
 )
 
 func TestTransformScoping(t *testing.T) {
@@ -106,7 +108,7 @@ func TestTransformScoping(t *testing.T) {
 			if err != nil {
 				t.Error(err)
 			}
-			ExtractStyles(doc)
+			ExtractStyles(doc, &TransformOptions{})
 			Transform(doc, TransformOptions{Scope: "XXXXXX"})
 			astro.PrintToSource(&b, doc.LastChild.FirstChild.NextSibling.FirstChild)
 			got := b.String()
@@ -186,7 +188,7 @@ func TestFullTransform(t *testing.T) {
 			if err != nil {
 				t.Error(err)
 			}
-			ExtractStyles(doc)
+			ExtractStyles(doc, &TransformOptions{})
 			// Clear doc.Styles to avoid scoping behavior, we're not testing that here
 			doc.Styles = make([]*astro.Node, 0)
 			Transform(doc, TransformOptions{})
@@ -225,6 +227,16 @@ func TestTransformTrailingSpace(t *testing.T) {
 			source: "<html><body>\n\n\n</body></html>",
 			want:   "<html><body>\n\n\n</body></html>",
 		},
+		{
+			name:   "pre with trailing space",
+			source: "<h1>Hello world</h1><pre>  a  \n\n</pre>\n\n",
+			want:   "<h1>Hello world</h1><pre>  a  \n\n</pre>",
+		},
+		{
+			name:   "explicit space expression",
+			source: "<h1>Hello world</h1>{\" \"}\n\n",
+			want:   "<h1>Hello world</h1>{\" \"}",
+		},
 	}
 	var b strings.Builder
 	for _, tt := range tests {
@@ -234,7 +246,7 @@ func TestTransformTrailingSpace(t *testing.T) {
 			if err != nil {
 				t.Error(err)
 			}
-			ExtractStyles(doc)
+			ExtractStyles(doc, &TransformOptions{})
 			// Clear doc.Styles to avoid scoping behavior, we're not testing that here
 			doc.Styles = make([]*astro.Node, 0)
 			Transform(doc, TransformOptions{})
@@ -347,7 +359,7 @@ func TestCompactTransform(t *testing.T) {
 			if err != nil {
 				t.Error(err)
 			}
-			ExtractStyles(doc)
+			ExtractStyles(doc, &TransformOptions{})
 			// Clear doc.Styles to avoid scoping behavior, we're not testing that here
 			doc.Styles = make([]*astro.Node, 0)
 			Transform(doc, TransformOptions{
@@ -361,3 +373,1070 @@ func TestCompactTransform(t *testing.T) {
 		})
 	}
 }
+
+func TestTransformComponentChildren(t *testing.T) {
+	tests := []struct {
+		name    string
+		source  string
+		styles  []string
+		scripts int
+		want    string
+	}{
+		{
+			name:   "style at slot root",
+			source: `<Card><style>.a{}</style><p>A</p></Card>`,
+			styles: []string{".a:where(.astro-XXXXXX){}"},
+			want:   `<Card class="astro-XXXXXX"><p class="astro-XXXXXX">A</p></Card>`,
+		},
+		{
+			name:   "style nested in slot content",
+			source: `<Card><div><style>.a{}</style><p>A</p></div></Card>`,
+			styles: []string{".a:where(.astro-XXXXXX){}"},
+			want:   `<Card class="astro-XXXXXX"><div class="astro-XXXXXX"><p class="astro-XXXXXX">A</p></div></Card>`,
+		},
+		{
+			name:   "slotted style keeps its position among other styles",
+			source: `<style>.a{}</style><Card><style>.b{}</style></Card><style>.c{}</style>`,
+			styles: []string{".c:where(.astro-XXXXXX){}", ".b:where(.astro-XXXXXX){}", ".a:where(.astro-XXXXXX){}"},
+			want:   `<Card class="astro-XXXXXX"></Card>`,
+		},
+		{
+			name:    "script at slot root",
+			source:  `<Card><script>console.log("a")</script><p>A</p></Card>`,
+			scripts: 1,
+			want:    `<Card><p>A</p></Card>`,
+		},
+		{
+			name:    "script nested in slot content",
+			source:  `<Card><div><script>console.log("a")</script></div></Card>`,
+			scripts: 1,
+			want:    `<Card><div></div></Card>`,
+		},
+		{
+			name:   "inline script stays in slot",
+			source: `<Card><script is:inline>console.log("a")</script></Card>`,
+			want:   `<Card><script is:inline>console.log("a")</script></Card>`,
+		},
+	}
+	var b strings.Builder
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			b.Reset()
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			ExtractStyles(doc, &TransformOptions{})
+			Transform(doc, TransformOptions{Scope: "XXXXXX"})
+			if len(tt.styles) != len(doc.Styles) {
+				t.Errorf("\nFAIL: %s\n  want %d styles, got %d", tt.name, len(tt.styles), len(doc.Styles))
+			} else {
+				for i, style := range doc.Styles {
+					if tt.styles[i] != style.FirstChild.Data {
+						t.Errorf("\nFAIL: %s\n  want style: %s\n  got:        %s", tt.name, tt.styles[i], style.FirstChild.Data)
+					}
+				}
+			}
+			if tt.scripts != len(doc.Scripts) {
+				t.Errorf("\nFAIL: %s\n  want %d scripts, got %d", tt.name, tt.scripts, len(doc.Scripts))
+			}
+			astro.PrintToSource(&b, doc)
+			got := strings.TrimSpace(b.String())
+			if tt.want != got {
+				t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, tt.want, got)
+			}
+		})
+	}
+}
+
+func TestExtractStylesInExpressions(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		styles []string
+		want   string
+	}{
+		{
+			name:   "ternary branches",
+			source: `{cond ? <div><style>.a{}</style></div> : <div><style>.b{}</style></div>}`,
+			styles: []string{".b:where(.astro-XXXXXX){}", ".a:where(.astro-XXXXXX){}"},
+			want:   `{cond ? <div class="astro-XXXXXX"></div> : <div class="astro-XXXXXX"></div>}`,
+		},
+		{
+			name:   "ternary branches after top-level style",
+			source: `<style>.a{}</style>{cond ? <div><style>.b{}</style></div> : <div><style>.c{}</style></div>}`,
+			styles: []string{".c:where(.astro-XXXXXX){}", ".b:where(.astro-XXXXXX){}", ".a:where(.astro-XXXXXX){}"},
+			want:   `{cond ? <div class="astro-XXXXXX"></div> : <div class="astro-XXXXXX"></div>}`,
+		},
+		{
+			name:   "direct expression child",
+			source: `{show && <style>.x{}</style>}`,
+			styles: []string{},
+			want:   `{show && <style>.x{}</style>}`,
+		},
+		{
+			name:   "map callback",
+			source: `<ul>{items.map(item => <li><style>.a{}</style>{item}</li>)}</ul>`,
+			styles: []string{".a:where(.astro-XXXXXX){}"},
+			want:   `<ul class="astro-XXXXXX">{items.map(item => <li class="astro-XXXXXX">{item}</li>)}</ul>`,
+		},
+	}
+	var b strings.Builder
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			b.Reset()
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			ExtractStyles(doc, &TransformOptions{})
+			Transform(doc, TransformOptions{Scope: "XXXXXX"})
+			if len(tt.styles) != len(doc.Styles) {
+				t.Errorf("\nFAIL: %s\n  want %d styles, got %d", tt.name, len(tt.styles), len(doc.Styles))
+			} else {
+				for i, style := range doc.Styles {
+					if tt.styles[i] != style.FirstChild.Data {
+						t.Errorf("\nFAIL: %s\n  want style: %s\n  got:        %s", tt.name, tt.styles[i], style.FirstChild.Data)
+					}
+				}
+			}
+			astro.PrintToSource(&b, doc)
+			got := strings.TrimSpace(b.String())
+			if tt.want != got {
+				t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, tt.want, got)
+			}
+		})
+	}
+}
+
+func TestExtractScriptAttributes(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   int
+	}{
+		{
+			name:   "plain",
+			source: `<script>console.log(1)</script>`,
+			want:   1,
+		},
+		{
+			name:   "src",
+			source: `<script src="./a.js"></script>`,
+			want:   1,
+		},
+		{
+			name:   "hoist",
+			source: `<script hoist>console.log(1)</script>`,
+			want:   1,
+		},
+		{
+			name:   "defer",
+			source: `<script defer>console.log(1)</script>`,
+			want:   0,
+		},
+		{
+			name:   "async src",
+			source: `<script async src="./a.js"></script>`,
+			want:   0,
+		},
+		{
+			name:   "module defer",
+			source: `<script type="module" defer>console.log(1)</script>`,
+			want:   0,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			walk(doc, func(n *astro.Node) {
+				ExtractScript(doc, n, &TransformOptions{})
+			})
+			if tt.want != len(doc.Scripts) {
+				t.Errorf("\nFAIL: %s\n  want %d hoisted scripts, got %d", tt.name, tt.want, len(doc.Scripts))
+			}
+		})
+	}
+}
+
+func TestCustomElements(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   []string
+	}{
+		{
+			name:   "none",
+			source: `<div><Component /></div>`,
+			want:   []string{},
+		},
+		{
+			name:   "authored order",
+			source: `<my-button>One</my-button><x-card><my-button>Two</my-button></x-card>`,
+			want:   []string{"my-button", "x-card"},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			Transform(doc, TransformOptions{})
+			if len(tt.want) != len(doc.CustomElements) {
+				t.Fatalf("\nFAIL: %s\n  want: %v\n  got:  %v", tt.name, tt.want, doc.CustomElements)
+			}
+			for i, tag := range doc.CustomElements {
+				if tt.want[i] != tag {
+					t.Errorf("\nFAIL: %s\n  want: %v\n  got:  %v", tt.name, tt.want, doc.CustomElements)
+				}
+			}
+		})
+	}
+}
+
+func TestCSSVariables(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   []string
+	}{
+		{
+			name:   "none",
+			source: `<style>div { color: red; }</style><div />`,
+			want:   []string{},
+		},
+		{
+			name:   "scoped",
+			source: `<style>div { color: var(--brand); }</style><div />`,
+			want:   []string{"--brand"},
+		},
+		{
+			name:   "fallback",
+			source: `<style>div { color: var( --brand, var(--fallback-color) ); }</style><div />`,
+			want:   []string{"--brand", "--fallback-color"},
+		},
+		{
+			name:   "authored order and deduplicated",
+			source: `<style>div { color: var(--a); }</style><style>p { color: var(--b); border-color: var(--a); }</style><div />`,
+			want:   []string{"--a", "--b"},
+		},
+		{
+			name:   "global",
+			source: `<style is:global>div { color: var(--global); }</style><style>p { color: var(--brand); }</style><div />`,
+			want:   []string{"--brand"},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			Transform(doc, TransformOptions{Scope: "XXXX"})
+			if len(tt.want) != len(doc.CSSVariables) {
+				t.Fatalf("\nFAIL: %s\n  want: %v\n  got:  %v", tt.name, tt.want, doc.CSSVariables)
+			}
+			for i, name := range doc.CSSVariables {
+				if tt.want[i] != name {
+					t.Errorf("\nFAIL: %s\n  want: %v\n  got:  %v", tt.name, tt.want, doc.CSSVariables)
+				}
+			}
+		})
+	}
+}
+
+func TestClientOnlyFrameworks(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   []string
+	}{
+		{
+			name:   "single",
+			source: `<Counter client:only="react" />`,
+			want:   []string{"react"},
+		},
+		{
+			name:   "deduplicated",
+			source: `<A client:only="react" /><B client:only="vue" /><C client:only="react" />`,
+			want:   []string{"react", "vue"},
+		},
+		{
+			name:   "expression",
+			source: `<Counter client:only={framework} />`,
+			want:   []string{"unknown"},
+		},
+		{
+			name:   "no hint",
+			source: `<Counter client:only /><Widget client:load />`,
+			want:   []string{},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			Transform(doc, TransformOptions{})
+			if len(tt.want) != len(doc.ClientOnlyFrameworks) {
+				t.Fatalf("\nFAIL: %s\n  want: %v\n  got:  %v", tt.name, tt.want, doc.ClientOnlyFrameworks)
+			}
+			for i, framework := range doc.ClientOnlyFrameworks {
+				if tt.want[i] != framework {
+					t.Errorf("\nFAIL: %s\n  want: %v\n  got:  %v", tt.name, tt.want, doc.ClientOnlyFrameworks)
+				}
+			}
+		})
+	}
+}
+
+func TestExtractStylesDynamicAttributes(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		styles int
+		want   string
+	}{
+		{
+			name:   "spread",
+			source: `<style {...styleAttrs}>.a{}</style><div />`,
+			styles: 0,
+			want:   `<style {...styleAttrs}>.a{}</style><div></div>`,
+		},
+		{
+			name:   "dynamic lang",
+			source: `<style lang={lang}>.a{}</style><div />`,
+			styles: 0,
+			want:   `<style lang={lang}>.a{}</style><div></div>`,
+		},
+		{
+			name:   "define:vars",
+			source: `<style define:vars={{ color }}>.a{}</style><div />`,
+			styles: 1,
+			want:   `<div></div>`,
+		},
+		{
+			name:   "is:global expression",
+			source: `<style is:global={true}>.a{}</style><div />`,
+			styles: 1,
+			want:   `<div></div>`,
+		},
+	}
+	var b strings.Builder
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			b.Reset()
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			ExtractStyles(doc, &TransformOptions{})
+			if tt.styles != len(doc.Styles) {
+				t.Errorf("\nFAIL: %s\n  want %d styles, got %d", tt.name, tt.styles, len(doc.Styles))
+			}
+			astro.PrintToSource(&b, doc)
+			got := strings.TrimSpace(b.String())
+			if tt.want != got {
+				t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, tt.want, got)
+			}
+		})
+	}
+}
+
+func TestSlottedStyleWarning(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "component child",
+			source: `<Card><style>.a{}</style></Card>`,
+			want:   "<style> inside of <Card> has been hoisted out of the component's slot and will be scoped to this file. Add `is:inline` to keep it in the slot.",
+		},
+		{
+			name:   "custom element child",
+			source: `<my-card><div><style>.a{}</style></div></my-card>`,
+			want:   "<style> inside of <my-card> has been hoisted out of the component's slot and will be scoped to this file. Add `is:inline` to keep it in the slot.",
+		},
+		{
+			name:   "inline",
+			source: `<Card><style is:inline>.a{}</style></Card>`,
+			want:   "",
+		},
+		{
+			name:   "top-level",
+			source: `<style>.a{}</style><Card />`,
+			want:   "",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			got := ""
+			walk(doc, func(n *astro.Node) {
+				if warning := SlottedStyleWarning(n); warning != "" {
+					got = warning
+				}
+			})
+			if tt.want != got {
+				t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, tt.want, got)
+			}
+		})
+	}
+}
+
+func TestDynamicStyleWarning(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "expression",
+			source: `<style lang={lang}>.a{}</style>`,
+			want:   "<style> has the dynamic attribute `lang` and will be rendered inline without scoping or preprocessing. Add `is:inline` to silence this warning.",
+		},
+		{
+			name:   "spread",
+			source: `<style { ...attrs }>.a{}</style>`,
+			want:   "<style> has the dynamic attribute `{...attrs}` and will be rendered inline without scoping or preprocessing. Add `is:inline` to silence this warning.",
+		},
+		{
+			name:   "define:vars",
+			source: `<style define:vars={{ color }}>.a{}</style>`,
+			want:   "",
+		},
+		{
+			name:   "is:global expression",
+			source: `<style is:global={true}>.a{}</style>`,
+			want:   "",
+		},
+		{
+			name:   "inline",
+			source: `<style is:inline lang={lang}>.a{}</style>`,
+			want:   "",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			got := ""
+			walk(doc, func(n *astro.Node) {
+				if warning := DynamicStyleWarning(n); warning != "" {
+					got = warning
+				}
+			})
+			if tt.want != got {
+				t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, tt.want, got)
+			}
+		})
+	}
+}
+
+func TestMetaTags(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   []astro.MetaTagMetadata
+	}{
+		{
+			name:   "charset",
+			source: `<head><meta charset="utf-8"></head>`,
+			want:   []astro.MetaTagMetadata{{TagType: "charset", Key: "utf-8"}},
+		},
+		{
+			name:   "viewport",
+			source: `<head><meta name="viewport" content="width=device-width"></head>`,
+			want:   []astro.MetaTagMetadata{{TagType: "name", Key: "viewport", Content: "width=device-width"}},
+		},
+		{
+			name:   "property and http-equiv",
+			source: `<head><meta property="og:title" content="Astro"><meta http-equiv="refresh" content="5"></head>`,
+			want: []astro.MetaTagMetadata{
+				{TagType: "property", Key: "og:title", Content: "Astro"},
+				{TagType: "http-equiv", Key: "refresh", Content: "5"},
+			},
+		},
+		{
+			name:   "dynamic",
+			source: `<head><meta name="description" content={description}><meta name={name} content="static"></head>`,
+			want:   []astro.MetaTagMetadata{},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			Transform(doc, TransformOptions{})
+			if len(tt.want) != len(doc.MetaTags) {
+				t.Fatalf("\nFAIL: %s\n  want %d meta tags, got %d", tt.name, len(tt.want), len(doc.MetaTags))
+			}
+			for i, meta := range doc.MetaTags {
+				if tt.want[i] != *meta {
+					t.Errorf("\nFAIL: %s\n  want: %+v\n  got:  %+v", tt.name, tt.want[i], *meta)
+				}
+			}
+		})
+	}
+}
+
+func TestContainsHead(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   bool
+	}{
+		{
+			name:   "no head",
+			source: `<div />`,
+			want:   false,
+		},
+		{
+			name:   "implicit head",
+			source: `<html><body><div /></body></html>`,
+			want:   false,
+		},
+		{
+			name:   "explicit head",
+			source: `<html><head><title>Hi</title></head><body /></html>`,
+			want:   true,
+		},
+		{
+			name:   "head in a conditional branch",
+			source: `{cond && <head><title>Hi</title></head>}<div />`,
+			want:   true,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			Transform(doc, TransformOptions{})
+			if tt.want != doc.ContainsHead {
+				t.Errorf("\nFAIL: %s\n  want: %v\n  got:  %v", tt.name, tt.want, doc.ContainsHead)
+			}
+		})
+	}
+}
+
+func TestFetchContentError(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "frontmatter",
+			source: "---\nconst posts = await Astro.fetchContent('*.md');\n---\n<div />",
+			want:   "Astro.fetchContent() has been removed. Use Astro.glob() or content collections instead.",
+		},
+		{
+			name:   "expression",
+			source: "<ul>{Astro.fetchContent('*.md').map((post) => <li>{post.title}</li>)}</ul>",
+			want:   "Astro.fetchContent() has been removed. Use Astro.glob() or content collections instead.",
+		},
+		{
+			name:   "other member",
+			source: "---\nconst fetchContent = () => {};\nconst posts = await Astro.glob('*.md');\n---\n<div />",
+			want:   "",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			got := FindFetchContentError(doc)
+			if tt.want != got {
+				t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, tt.want, got)
+			}
+		})
+	}
+}
+
+func TestFindMixedHydration(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   []string // The name of each mixed component, prefixed with ! for errors
+		lines  []string // Positions expected in the message of the first component
+	}{
+		{
+			name:   "hydrated and static",
+			source: "<Chart />\n<Chart client:load />",
+			want:   []string{"Chart"},
+			lines:  []string{"at 2:", "at 1:"},
+		},
+		{
+			name:   "client:only and static",
+			source: "<div>\n\t<Chart client:only=\"react\" />\n</div>\n<Chart />",
+			want:   []string{"!Chart"},
+			lines:  []string{"at 2:", "at 4:"},
+		},
+		{
+			name:   "client:only is preferred",
+			source: "<Chart client:load />\n<Chart />\n<Chart client:only=\"react\" />",
+			want:   []string{"!Chart"},
+			lines:  []string{"at 3:", "at 2:"},
+		},
+		{
+			name:   "authored order",
+			source: "<B client:idle /><A /><A client:visible /><B />",
+			want:   []string{"B", "A"},
+		},
+		{
+			name:   "consistent usage",
+			source: "<A /><A /><B client:load /><B client:idle /><C client:only /><div />",
+			want:   []string{},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			mixed := FindMixedHydration(doc)
+			got := make([]string, 0)
+			for _, m := range mixed {
+				if m.IsError() {
+					got = append(got, "!"+m.Name)
+				} else {
+					got = append(got, m.Name)
+				}
+			}
+			if strings.Join(tt.want, ",") != strings.Join(got, ",") {
+				t.Fatalf("\nFAIL: %s\n  want: %v\n  got:  %v", tt.name, tt.want, got)
+			}
+			if len(tt.lines) > 0 {
+				message := mixed[0].Message(tt.source)
+				// The hydrated usage is referenced first
+				hydrated := strings.Index(message, tt.lines[0])
+				static := strings.Index(message, tt.lines[1])
+				if hydrated == -1 || static == -1 || hydrated > static {
+					t.Errorf("\nFAIL: %s\n  want positions: %v\n  got:  %s", tt.name, tt.lines, message)
+				}
+			}
+		})
+	}
+}
+
+func TestFindDuplicateDocumentElements(t *testing.T) {
+	// The parser already merges most duplicates, so these trees are built by hand
+	el := func(tag a.Atom, children ...*astro.Node) *astro.Node {
+		n := &astro.Node{Type: astro.ElementNode, DataAtom: tag, Data: tag.String()}
+		for _, c := range children {
+			n.AppendChild(c)
+		}
+		return n
+	}
+	component := func(children ...*astro.Node) *astro.Node {
+		n := &astro.Node{Type: astro.ElementNode, Data: "Layout", Component: true}
+		for _, c := range children {
+			n.AppendChild(c)
+		}
+		return n
+	}
+	tests := []struct {
+		name string
+		doc  *astro.Node
+		want []string
+	}{
+		{
+			name: "single document",
+			doc:  el(a.Html, el(a.Head, el(a.Title)), el(a.Body, el(a.Div))),
+			want: []string{},
+		},
+		{
+			name: "head in component in body",
+			doc:  el(a.Html, el(a.Head), el(a.Body, component(el(a.Head, el(a.Meta))))),
+			want: []string{"<head> at an unknown position is inside of <Layout> at an unknown position. Head content is rendered where the first <head> closes, so <head> should be a direct child of <html>."},
+		},
+		{
+			name: "head in body",
+			doc:  el(a.Html, el(a.Body, el(a.Head))),
+			want: []string{"<head> at an unknown position is inside of <body> at an unknown position. Head content is rendered where the first <head> closes, so <head> should be a direct child of <html>."},
+		},
+		{
+			name: "head in component",
+			doc:  component(el(a.Head, el(a.Title))),
+			want: []string{"<head> at an unknown position is inside of <Layout> at an unknown position. Head content is rendered where the first <head> closes, so <head> should be a direct child of <html>."},
+		},
+		{
+			name: "second head",
+			doc:  el(a.Html, el(a.Head), el(a.Head)),
+			want: []string{"<head> at an unknown position comes after the <head> at an unknown position. Only the first <head> renders head content, so a document should only have one <head>."},
+		},
+		{
+			name: "nested html",
+			doc:  el(a.Html, el(a.Body, component(el(a.Html)))),
+			want: []string{"<html> at an unknown position is nested inside of the <html> at an unknown position. A document should only have one <html>."},
+		},
+		{
+			name: "head after body",
+			doc:  el(a.Html, el(a.Body), el(a.Head)),
+			want: []string{"<head> at an unknown position comes after the <body> at an unknown position. Head content is rendered where the first <head> closes, so it should come before <body>."},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got := make([]string, 0)
+			for _, d := range FindDuplicateDocumentElements(tt.doc) {
+				got = append(got, d.Message(""))
+			}
+			if strings.Join(tt.want, "\n") != strings.Join(got, "\n") {
+				t.Errorf("\nFAIL: %s\n  want: %v\n  got:  %v", tt.name, tt.want, got)
+			}
+		})
+	}
+}
+
+func TestDuplicateDocumentElementPositions(t *testing.T) {
+	source := "<head></head>\n<head></head>"
+	first := &astro.Node{Type: astro.ElementNode, DataAtom: a.Head, Data: "head", Loc: []loc.Loc{{Start: 0}}}
+	second := &astro.Node{Type: astro.ElementNode, DataAtom: a.Head, Data: "head", Loc: []loc.Loc{{Start: 14}}}
+	doc := &astro.Node{Type: astro.DocumentNode}
+	doc.AppendChild(first)
+	doc.AppendChild(second)
+
+	duplicates := FindDuplicateDocumentElements(doc)
+	if len(duplicates) != 1 {
+		t.Fatalf("expected one duplicate, got %d", len(duplicates))
+	}
+	want := "<head> at 2:1 comes after the <head> at 1:1. Only the first <head> renders head content, so a document should only have one <head>."
+	if got := duplicates[0].Message(source); got != want {
+		t.Errorf("\n  want: %s\n  got:  %s", want, got)
+	}
+}
+
+func TestTransitionConflictWarning(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "differing values",
+			source: `<div transition:persist="a" transition:name="b"></div>`,
+			want:   `<div> has both transition:persist="a" and transition:name="b". The element will be persisted as "a", transition:name only names its animation.`,
+		},
+		{
+			name:   "same values",
+			source: `<div transition:persist="a" transition:name="a"></div>`,
+			want:   "",
+		},
+		{
+			name:   "empty persist",
+			source: `<div transition:persist transition:name="b"></div>`,
+			want:   "",
+		},
+		{
+			name:   "persist only",
+			source: `<div transition:persist="a"></div>`,
+			want:   "",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			got := ""
+			walk(doc, func(n *astro.Node) {
+				if warning := TransitionConflictWarning(n); warning != "" {
+					got = warning
+				}
+			})
+			if tt.want != got {
+				t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, tt.want, got)
+			}
+		})
+	}
+}
+
+func TestHTMLNestingWarning(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "div in ul",
+			source: `<ul><div>x</div></ul>`,
+			want:   `<div> is not a valid child of <ul>.`,
+		},
+		{
+			name:   "li in div",
+			source: `<div><li>x</li></div>`,
+			want:   `<li> is not a valid child of <div>.`,
+		},
+		{
+			name:   "option in div",
+			source: `<div><option>x</option></div>`,
+			want:   `<option> is not a valid child of <div>.`,
+		},
+		{
+			name:   "li in ul",
+			source: `<ul><li>x</li></ul>`,
+			want:   "",
+		},
+		{
+			name:   "li at the top level",
+			source: `<li>x</li>`,
+			want:   "",
+		},
+		{
+			name:   "li in component",
+			source: `<List><li>x</li></List>`,
+			want:   "",
+		},
+		{
+			name:   "li in expression",
+			source: `<div>{items.map(item => <li>{item}</li>)}</div>`,
+			want:   "",
+		},
+		{
+			name:   "slot in ul",
+			source: `<ul><slot /></ul>`,
+			want:   "",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			got := ""
+			walk(doc, func(n *astro.Node) {
+				if warning := HTMLNestingWarning(n); warning != "" {
+					got = warning
+				}
+			})
+			if tt.want != got {
+				t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, tt.want, got)
+			}
+		})
+	}
+}
+
+func TestResolvedImports(t *testing.T) {
+	source := `---
+import Card from '../components/Card.astro';
+import { Button } from '../components/Button.jsx';
+import Unused from '../components/Unused.astro';
+import Package from 'some-package';
+---
+<Card /><Card /><Button /><Package />`
+	doc, err := astro.Parse(strings.NewReader(source))
+	if err != nil {
+		t.Error(err)
+	}
+	Transform(doc, TransformOptions{Pathname: "/src/pages/index.astro"})
+	want := map[string]string{
+		"../components/Card.astro": "/src/components/Card.astro",
+		"../components/Button.jsx": "/src/components/Button.jsx",
+	}
+	if len(want) != len(doc.ResolvedImports) {
+		t.Fatalf("want: %v\ngot:  %v", want, doc.ResolvedImports)
+	}
+	for specifier, resolved := range want {
+		if doc.ResolvedImports[specifier] != resolved {
+			t.Errorf("want: %v\ngot:  %v", want, doc.ResolvedImports)
+		}
+	}
+}
+
+func TestExtractScriptTypes(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   int
+	}{
+		{
+			name:   "plain",
+			source: `<script>console.log("hi")</script>`,
+			want:   1,
+		},
+		{
+			name:   "direct expression child",
+			source: `{show && <script>console.log("hi")</script>}`,
+			want:   0,
+		},
+		{
+			name:   "importmap",
+			source: `<script type="importmap">{"imports":{}}</script>`,
+			want:   0,
+		},
+		{
+			name:   "importmap with hoist",
+			source: `<script type="importmap" hoist>{"imports":{}}</script>`,
+			want:   0,
+		},
+		{
+			name:   "module/shim with hoist",
+			source: `<script type="module/shim" src="es-shim.js" hoist></script>`,
+			want:   0,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			Transform(doc, TransformOptions{})
+			if len(doc.Scripts) != tt.want {
+				t.Errorf("\nFAIL: %s\n  want: %d scripts\n  got:  %d", tt.name, tt.want, len(doc.Scripts))
+			}
+		})
+	}
+}
+
+func TestExpressionChildWarning(t *testing.T) {
+	tests := []struct {
+		name   string
+		source string
+		want   string
+	}{
+		{
+			name:   "style",
+			source: `{show && <style>.x{}</style>}`,
+			want:   "<style> inside of an expression can't be hoisted and will be rendered inline without processing. Wrap it in an element, or add `is:inline` to silence this warning.",
+		},
+		{
+			name:   "script",
+			source: `{show && <script>console.log("hi")</script>}`,
+			want:   "<script> inside of an expression can't be hoisted and will be rendered inline without processing. Wrap it in an element, or add `is:inline` to silence this warning.",
+		},
+		{
+			name:   "inline style",
+			source: `{show && <style is:inline>.x{}</style>}`,
+			want:   "",
+		},
+		{
+			name:   "json script",
+			source: `{show && <script type="application/ld+json">{}</script>}`,
+			want:   "",
+		},
+		{
+			name:   "wrapped style",
+			source: `{show && <div><style>.x{}</style></div>}`,
+			want:   "",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			doc, err := astro.Parse(strings.NewReader(tt.source))
+			if err != nil {
+				t.Error(err)
+			}
+			got := ""
+			walk(doc, func(n *astro.Node) {
+				if warning := ExpressionChildWarning(n); warning != "" {
+					got = warning
+				}
+			})
+			if tt.want != got {
+				t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, tt.want, got)
+			}
+		})
+	}
+}
+
+func TestNormalizeFilename(t *testing.T) {
+	tests := []struct {
+		name     string
+		filename string
+		root     string
+		want     string
+	}{
+		{
+			name:     "posix",
+			filename: "/home/user/site/src/pages/index.astro",
+			root:     "/home/user/site",
+			want:     "/src/pages/index.astro",
+		},
+		{
+			name:     "trailing slash",
+			filename: "/home/user/site/src/pages/index.astro",
+			root:     "/home/user/site/",
+			want:     "/src/pages/index.astro",
+		},
+		{
+			name:     "windows",
+			filename: `C:\Users\user\site\src\pages\index.astro`,
+			root:     `C:\Users\user\site`,
+			want:     "/src/pages/index.astro",
+		},
+		{
+			name:     "windows drive letter case",
+			filename: `c:\Users\user\site\src\pages\index.astro`,
+			root:     "C:/Users/user/site",
+			want:     "/src/pages/index.astro",
+		},
+		{
+			name:     "sibling directory",
+			filename: "/home/user/site-2/src/pages/index.astro",
+			root:     "/home/user/site",
+			want:     "/home/user/site-2/src/pages/index.astro",
+		},
+		{
+			name:     "outside of root",
+			filename: `D:\other\index.astro`,
+			root:     `C:\Users\user\site`,
+			want:     "d:/other/index.astro",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got := NormalizeFilename(tt.filename, tt.root)
+			if tt.want != got {
+				t.Errorf("\nFAIL: %s\n  want: %s\n  got:  %s", tt.name, tt.want, got)
+			}
+		})
+	}
+}
+
+func TestHydratedComponentsDeduplicated(t *testing.T) {
+	source := `---
+import Counter from '../components/Counter.jsx';
+import Widget from '../components/Widget.jsx';
+---
+<Counter client:load /><Counter client:load /><Counter client:load /><Widget client:only /><Widget client:only />`
+	doc, err := astro.Parse(strings.NewReader(source))
+	if err != nil {
+		t.Error(err)
+	}
+	Transform(doc, TransformOptions{})
+	if len(doc.HydrationDirectives) != 2 {
+		t.Errorf("expected 2 hydration directives, got %v", doc.HydrationDirectives)
+	}
+	if len(doc.HydratedComponentNodes) != 1 || doc.HydratedComponentNodes[0].CustomElement {
+		t.Errorf("expected Counter to be listed once, got %d nodes", len(doc.HydratedComponentNodes))
+	}
+	if len(doc.HydratedComponents) != 1 || doc.HydratedComponents[0].Specifier != "../components/Counter.jsx" {
+		t.Errorf("expected one hydrated component, got %d", len(doc.HydratedComponents))
+	}
+	if len(doc.ClientOnlyComponents) != 1 || doc.ClientOnlyComponents[0].Specifier != "../components/Widget.jsx" {
+		t.Errorf("expected one client:only component, got %d", len(doc.ClientOnlyComponents))
+	}
+}
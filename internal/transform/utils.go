@@ -1,6 +1,8 @@
 package transform
 
 import (
+	"strings"
+
 	astro "github.com/withastro/compiler/internal"
 	"golang.org/x/net/html/atom"
 )
@@ -8,9 +10,9 @@ import (
 func hasTruthyAttr(n *astro.Node, key string) bool {
 	for _, attr := range n.Attr {
 		if attr.Key == key &&
-			(attr.Type == astro.EmptyAttribute) ||
-			(attr.Type == astro.ExpressionAttribute && attr.Val == "true") ||
-			(attr.Type == astro.QuotedAttribute && (attr.Val == "" || attr.Val == "true")) {
+			((attr.Type == astro.EmptyAttribute) ||
+				(attr.Type == astro.ExpressionAttribute && attr.Val == "true") ||
+				(attr.Type == astro.QuotedAttribute && (attr.Val == "" || attr.Val == "true"))) {
 			return true
 		}
 	}
@@ -43,13 +45,83 @@ func GetAttr(n *astro.Node, key string) *astro.Attribute {
 	return nil
 }
 
+// Only scripts without attributes (other than `src`) are hoisted, unless they
+// use the legacy `hoist` attribute
+func hasHoistableScriptAttrs(n *astro.Node) bool {
+	return hasTruthyAttr(n, "hoist") || len(n.Attr) == 0 || (len(n.Attr) == 1 && n.Attr[0].Key == "src")
+}
+
 func IsHoistable(n *astro.Node) bool {
+	return isHoistable(n, false)
+}
+
+// Every <style> that ends up in `doc.Styles` goes through this check. Style
+// preprocessing, CSS printing and scoping all index into that same list, so
+// any new exception must be added here rather than at a call site.
+func ShouldExtractStyle(n *astro.Node) bool {
+	if n.Type != astro.ElementNode || n.DataAtom != atom.Style {
+		return false
+	}
+	if HasSetDirective(n) || HasInlineDirective(n) {
+		return false
+	}
+	if GetDynamicStyleAttr(n) != nil {
+		return false
+	}
+	// Ignore styles in svg/noscript/etc
+	return IsHoistableStyle(n)
+}
+
+// Preprocessors only receive static attributes, so a <style> with a spread or
+// expression attribute (like `lang={lang}`) can't be extracted correctly.
+// `define:vars` and `is:*` directives (see hasTruthyAttr) are the only
+// expression attributes that are handled statically.
+func GetDynamicStyleAttr(n *astro.Node) *astro.Attribute {
+	for _, attr := range n.Attr {
+		switch attr.Type {
+		case astro.SpreadAttribute:
+			return &attr
+		case astro.ExpressionAttribute, astro.ShorthandAttribute, astro.TemplateLiteralAttribute:
+			if attr.Key != "define:vars" && !strings.HasPrefix(attr.Key, "is:") {
+				return &attr
+			}
+		}
+	}
+	return nil
+}
+
+// Like IsHoistable, but styles nested in an element inside of an expression
+// (like either branch of a ternary) are also hoistable. A <style> that is
+// itself the result of an expression can't be removed without breaking it.
+func IsHoistableStyle(n *astro.Node) bool {
+	return isHoistable(n, true)
+}
+
+// Elements inside of <svg>, <noscript> or <template> stay where they are
+func isHoistable(n *astro.Node, inExpression bool) bool {
+	// Removing the direct result of an expression would leave it empty
+	if n.Parent != nil && n.Parent.Expression {
+		return false
+	}
 	parent := n.Closest(func(p *astro.Node) bool {
+		if p.DataAtom == atom.Template && p.Expression {
+			return !inExpression
+		}
 		return p.DataAtom == atom.Svg || p.DataAtom == atom.Noscript || p.DataAtom == atom.Template
 	})
 	return parent == nil
 }
 
+// Returns the closest component or custom element that n is slotted into, if any
+func ClosestComponent(n *astro.Node) *astro.Node {
+	if n.Parent == nil {
+		return nil
+	}
+	return n.Parent.Closest(func(p *astro.Node) bool {
+		return p.Component || p.CustomElement
+	})
+}
+
 func IsImplictNode(n *astro.Node) bool {
 	return HasAttr(n, astro.ImplicitNodeMarker)
 }
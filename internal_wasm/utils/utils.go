@@ -50,3 +50,17 @@ func GetAttrs(n *astro.Node) js.Value {
 	}
 	return attrs
 }
+
+// Like GetAttrs, but as a plain map. Empty attributes have an empty string value.
+func GetAttrsMap(n *astro.Node) map[string]string {
+	attrs := make(map[string]string)
+	for _, attr := range n.Attr {
+		switch attr.Type {
+		case astro.QuotedAttribute:
+			attrs[attr.Key] = attr.Val
+		case astro.EmptyAttribute:
+			attrs[attr.Key] = ""
+		}
+	}
+	return attrs
+}
@@ -2,13 +2,28 @@ package css_printer
 
 import (
 	"fmt"
+	"strings"
 
 	"github.com/withastro/compiler/lib/esbuild/css_ast"
 	"github.com/withastro/compiler/lib/esbuild/css_lexer"
 )
 
+// Supported values for `Options.ScopeStrategy`
+const (
+	ScopeStrategyWhere     = "where"
+	ScopeStrategyClass     = "class"
+	ScopeStrategyAttribute = "attribute"
+)
+
 func (p *printer) printScopedSelector() bool {
-	p.print(fmt.Sprintf(":where(.astro-%s)", p.options.Scope))
+	switch p.options.ScopeStrategy {
+	case ScopeStrategyClass:
+		p.print(fmt.Sprintf(".astro-%s", p.options.Scope))
+	case ScopeStrategyAttribute:
+		p.print(fmt.Sprintf("[data-astro-cid-%s]", p.options.Scope))
+	default:
+		p.print(fmt.Sprintf(":where(.astro-%s)", p.options.Scope))
+	}
 	return true
 }
 
@@ -122,6 +137,11 @@ func (p *printer) printCompoundSelector(sel css_ast.CompoundSelector, isFirst bo
 			if s.Name == "global" || s.Name == "root" {
 				scoped = true
 			}
+			// `:where(:root)` and `:is(html)` match the same element as a bare `:root`
+			// or `html`, which are never scoped either
+			if (s.Name == "where" || s.Name == "is") && onlyMatchesDocumentRoot(s.Args) {
+				scoped = true
+			}
 		}
 	}
 
@@ -136,6 +156,28 @@ func (p *printer) printCompoundSelector(sel css_ast.CompoundSelector, isFirst bo
 	}
 }
 
+// Whether every selector in the arguments of a pseudo-class is `:root` or `html`
+func onlyMatchesDocumentRoot(args []css_ast.Token) bool {
+	if len(args) == 0 {
+		return false
+	}
+	start := 0
+	for i := 0; i <= len(args); i++ {
+		if i < len(args) && args[i].Kind != css_lexer.TComma {
+			continue
+		}
+		selector := args[start:i]
+		switch {
+		case len(selector) == 1 && selector[0].Kind == css_lexer.TIdent && strings.EqualFold(selector[0].Text, "html"):
+		case len(selector) == 2 && selector[0].Kind == css_lexer.TColon && selector[1].Kind == css_lexer.TIdent && strings.EqualFold(selector[1].Text, "root"):
+		default:
+			return false
+		}
+		start = i + 1
+	}
+	return true
+}
+
 func (p *printer) printPseudoClassSelector(pseudo css_ast.SSPseudoClass, whitespace trailingWhitespace) {
 	if pseudo.Name == "global" {
 		if len(pseudo.Args) > 0 {
@@ -9,9 +9,14 @@ export const parse: typeof types.parse = (input, options) => {
   return ensureServiceIsRunning().parse(input, options);
 };
 
+export const transformCSS: typeof types.transformCSS = (input, options) => {
+  return ensureServiceIsRunning().transformCSS(input, options);
+};
+
 interface Service {
   transform: typeof types.transform;
   parse: typeof types.parse;
+  transformCSS: typeof types.transformCSS;
 }
 
 let initializePromise: Promise<Service> | undefined;
@@ -64,5 +69,6 @@ const startRunningService = async (wasmURL: string): Promise<Service> => {
   return {
     transform: (input, options) => new Promise((resolve) => resolve(service.transform(input, options || {}))),
     parse: (input, options) => new Promise((resolve) => resolve(service.parse(input, options || {}))).then((result: any) => ({ ...result, ast: JSON.parse(result.ast) })),
+    transformCSS: (input, options) => new Promise((resolve) => resolve(service.transformCSS(input, options || {}))),
   };
 };
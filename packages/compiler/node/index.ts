@@ -1,4 +1,4 @@
-export type { PreprocessorResult, ParseOptions, TransformOptions, HoistedScript, TransformResult, ParseResult } from '../shared/types';
+export type { PreprocessorResult, ParseOptions, TransformOptions, TransformCSSOptions, TransformCSSResult, HoistedScript, TransformResult, ParseResult } from '../shared/types';
 import type * as types from '../shared/types';
 import { promises as fs } from 'fs';
 import Go from './wasm_exec.js';
@@ -16,6 +16,10 @@ export const convertToTSX: typeof types.convertToTSX = async (input, options) =>
   return getService().then((service) => service.convertToTSX(input, options));
 };
 
+export const transformCSS: typeof types.transformCSS = async (input, options) => {
+  return getService().then((service) => service.transformCSS(input, options));
+};
+
 export const compile = async (template: string): Promise<string> => {
   const { default: mod } = await import(`data:text/javascript;charset=utf-8;base64,${Buffer.from(template).toString('base64')}`);
   return mod;
@@ -25,6 +29,7 @@ interface Service {
   transform: typeof types.transform;
   parse: typeof types.parse;
   convertToTSX: typeof types.convertToTSX;
+  transformCSS: typeof types.transformCSS;
 }
 
 let longLivedService: Promise<Service> | undefined;
@@ -71,5 +76,6 @@ const startRunningService = async (): Promise<Service> => {
       }),
     parse: (input, options) => new Promise((resolve) => resolve(_service.parse(input, options || {}))).then((result: any) => ({ ...result, ast: JSON.parse(result.ast) })),
     convertToTSX: (input, options) => new Promise((resolve) => resolve(_service.convertToTSX(input, options || {}))),
+    transformCSS: (input, options) => new Promise((resolve) => resolve(_service.transformCSS(input, options || {}))),
   };
 };
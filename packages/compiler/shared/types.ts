@@ -25,9 +25,55 @@ export interface TransformOptions {
   projectRoot?: string;
   preprocessStyle?: (content: string, attrs: Record<string, string>) => Promise<PreprocessorResult>;
   experimentalStaticExtraction?: boolean;
+  /** Accept hot module updates via `import.meta.hot` in development */
+  hmr?: boolean;
+  /** Remove `on*` event handler attributes from HTML elements, for static output like emails */
+  stripEventHandlers?: boolean;
+  /** Set to `false` to keep HTML comments passed to components, in the slot of the content that follows them (or precedes them, for trailing comments). Defaults to `true`. */
+  stripSlotComments?: boolean;
+  /** Replace member expressions in the frontmatter and template expressions, like `{ 'import.meta.env.SSR': 'true' }` */
+  define?: Record<string, string>;
+  /** Inline scripts with fewer characters than this are rendered in place as `<script type="module">` instead of being hoisted */
+  inlineScriptThreshold?: number;
+  /** The `lang.*` suffix of the virtual style imports, like `scss` for `?astro&type=style&index=0&lang.scss`. Defaults to the `lang` of each `<style>`, or `css`. */
+  outputExtension?: string;
+  /** Warn about clearly invalid element nesting, like a `<div>` directly inside of a `<ul>` */
+  validateHTMLNesting?: boolean;
+  /** Skip the `internalURL` import and virtual style imports, for environments that provide the runtime functions as globals */
+  disableRuntimeImports?: boolean;
+  /** Only parse the source, and return the same AST as `parse` as JSON in `code`. Every other field of the result is left empty. */
+  parseOnly?: boolean;
+  /** Adds a `@jsxImportSource` pragma to the output, like `preact`, for JSX in expressions that is left to the downstream bundler */
+  jsxImportSource?: string;
+  /** The `sourcefile` relative to the project root, like `/src/pages/index.astro`, used in the virtual style imports */
+  normalizedFilename?: string;
+  /** The project root directory, used to derive `normalizedFilename` from `sourcefile` when it isn't passed */
+  root?: string;
+  /** Names the preprocessor for each `<style lang>`, like `{ scss: 'sass' }`, returned as `preprocessor` in `styleBlocks` */
+  stylePreprocessorMap?: Record<string, string>;
+  /** Print each scoped `<style>` at the start of the template (inside `<head>` for pages) instead of importing or extracting it, so `css` is always empty. Use `is:global` to opt a style out of scoping. */
+  emitCSSInline?: boolean;
+  /** Names of component slots which receive the render result, printed as `($$result) =>` instead of `() =>`, like `['fallback']` */
+  scopedSlotNames?: string[];
 }
 
-export type HoistedScript = { type: string } & (
+export interface TransformCSSOptions {
+  scope: string;
+  strategy?: 'where' | 'class' | 'attribute';
+}
+
+export interface TransformCSSResult {
+  code: string;
+  errors: string[];
+  /** The class, or attribute for the `attribute` strategy, that elements need to match the scoped selectors in `code` */
+  scopeClass: string;
+}
+
+export type HoistedScript = {
+  type: string;
+  /** Static attributes of the original `<script>`, like `defer` or `data-*`. Empty attributes have an empty string value. */
+  attrs: Record<string, string>;
+} & (
   | {
       type: 'external';
       src: string;
@@ -45,14 +91,78 @@ export interface HydratedComponent {
   resolvedPath: string;
 }
 
+export interface ModuleImport {
+  specifier: string;
+  /** Import attributes, like `{ type: 'json' }`. Only string values are included */
+  attributes: Record<string, string>;
+  /** Whether this is a bare stylesheet import, like `import './global.css'` */
+  isCssImport: boolean;
+  isTypeOnly: boolean;
+}
+
+export interface MetaTag {
+  tagType: 'charset' | 'name' | 'property' | 'http-equiv';
+  /** The value of the `tagType` attribute, like `utf-8` or `viewport` */
+  key: string;
+  content: string;
+}
+
+export interface StyleBlock {
+  /** The same CSS as the matching entry of `css` */
+  content: string;
+  /** The `lang` attribute of the style, or an empty string */
+  lang: string;
+  /** `true` when the style uses `is:global` and is not scoped */
+  isGlobal: boolean;
+  hasDefineVars: boolean;
+  /** The `define:vars` value as a JS expression, or an empty string */
+  defineVarsSource: string;
+  /** The `stylePreprocessorMap` entry for the style's `lang`, or an empty string */
+  preprocessor: string;
+}
+
 export interface TransformResult {
   css: string[];
+  /** Attributes of the `<style>` element that produced each entry of `css`. Empty attributes have an empty string value. */
+  cssAttrs: Record<string, string>[];
+  /** Details about the `<style>` element that produced each entry of `css` */
+  styleBlocks: StyleBlock[];
   scripts: HoistedScript[];
   hydratedComponents: HydratedComponent[];
   clientOnlyComponents: HydratedComponent[];
+  /** Framework hint of every `client:only` directive, like `react`. Dynamic hints are listed as `unknown` */
+  clientOnlyFrameworks: string[];
   code: string;
   map: string;
   scope: string;
+  /** The class added to scoped elements, like `astro-<scope>` */
+  scopeClass: string;
+  /** Stable hash of each extracted CSS block, in the same order as `css` */
+  cssHashes: string[];
+  /** Stable hash of each hoisted script's content (or `src`), in the same order as `scripts` */
+  scriptHashes: string[];
+  /** Stable hash of `code` without its styles, hoisted scripts or scope, so it only changes when the template does */
+  templateHash: string;
+  /** Tag name of every custom element used in the template, in authored order */
+  customElements: string[];
+  /** Custom properties referenced with `var()` by scoped styles, in authored order */
+  cssVariables: string[];
+  /** SSR-only `Astro` APIs used by the component, like `Astro.cookies` or `Astro.redirect` */
+  astroAPIUsage: string[];
+  /** Static `<meta>` tags in the template, so they can be deduplicated in `<head>` */
+  metaTags: MetaTag[];
+  /** `true` when the component has any extracted `<style>` elements */
+  hasStyles: boolean;
+  /** `true` when the component has any hoisted `<script>` elements */
+  hasScripts: boolean;
+  /** `true` when the template has an explicit `<head>` element, even inside of an expression */
+  containsHead: boolean;
+  /** The variable name of the generated component, like `$$Component`, derived from `pathname` */
+  componentName: string;
+  /** Maps the import specifier of every component used in the template to its path resolved against `pathname` */
+  resolvedImports: Record<string, string>;
+  /** Every import statement of the frontmatter, in source order */
+  modules: ModuleImport[];
 }
 
 export interface TSXResult {
@@ -77,6 +187,10 @@ export declare function parse(input: string, options?: ParseOptions): Promise<Pa
 
 export declare function convertToTSX(input: string, options?: { sourcefile?: string }): Promise<TSXResult>;
 
+// Scopes an arbitrary CSS string with the given scope, outside of a full `transform`.
+// This is useful when styles need to be re-scoped after running a preprocessor.
+export declare function transformCSS(input: string, options: TransformCSSOptions): Promise<TransformCSSResult>;
+
 // This configures the browser-based version of astro. It is necessary to
 // call this first and wait for the returned promise to be resolved before
 // making other API calls when using astro in the browser.
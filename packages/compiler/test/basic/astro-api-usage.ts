@@ -0,0 +1,25 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `
+---
+const session = Astro.cookies.get('x');
+if (!session) {
+  return Astro.redirect('/');
+}
+---
+
+<div>{Astro.locals.user}</div>
+`;
+
+let result;
+test.before(async () => {
+  result = await transform(FIXTURE);
+});
+
+test('reports SSR-only Astro APIs', () => {
+  assert.equal(result.astroAPIUsage, ['Astro.cookies', 'Astro.redirect', 'Astro.locals']);
+});
+
+test.run();
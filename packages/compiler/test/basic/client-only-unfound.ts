@@ -20,6 +20,7 @@ test.before(async () => {
   try {
     await transform(FIXTURE, {
       pathname: '/src/components/Cool.astro',
+      sourcefile: 'Cool.astro',
     });
   } catch (err) {
     error = err;
@@ -30,6 +31,15 @@ test('got an error because client:only component not found import', () => {
   assert.ok(error, 'paniced');
 });
 
+test('error includes the filename and the reason', () => {
+  assert.match(error.message, 'Cool.astro: Unable to find matching import statements for the client:only component: MyComponent.');
+});
+
+test('later transforms still work', async () => {
+  const result = await transform(`<div>Hello world!</div>`);
+  assert.match(result.code, '<div>Hello world!</div>');
+});
+
 /*
 test('exports named component', () => {
   assert.match(result.code, 'export default $$Cool', 'Expected output to contain named export');
@@ -83,4 +83,15 @@ test('ClientOnly components: named export', () => {
   assert.equal(components[2].resolvedPath, '/@fs/users/astro/apps/pacman/src/components/six.jsx');
 });
 
+test('Resolved imports', () => {
+  assert.equal(result.resolvedImports, {
+    '../components/one.jsx': '/@fs/users/astro/apps/pacman/src/components/one.jsx',
+    '../components/two.jsx': '/@fs/users/astro/apps/pacman/src/components/two.jsx',
+    '../components/three.tsx': '/@fs/users/astro/apps/pacman/src/components/three.tsx',
+    '../components/four.jsx': '/@fs/users/astro/apps/pacman/src/components/four.jsx',
+    '../components/five.jsx': '/@fs/users/astro/apps/pacman/src/components/five.jsx',
+    '../components/six.jsx': '/@fs/users/astro/apps/pacman/src/components/six.jsx',
+  });
+});
+
 test.run();
@@ -15,4 +15,15 @@ test('exports named component', () => {
   assert.match(result.code, 'export default $$Cool', 'Expected output to contain named export');
 });
 
+test('returns the component name', () => {
+  assert.equal(result.componentName, '$$Cool');
+});
+
+test('returns the component name for nested pathnames', async () => {
+  const nested = await transform(FIXTURE, {
+    pathname: 'src/pages/blog/PostDetail.astro',
+  });
+  assert.equal(nested.componentName, '$$PostDetail');
+});
+
 test.run();
@@ -0,0 +1,21 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `
+<my-button>One</my-button>
+<x-card>
+  <my-button>Two</my-button>
+</x-card>
+`;
+
+let result;
+test.before(async () => {
+  result = await transform(FIXTURE);
+});
+
+test('reports custom elements', () => {
+  assert.equal(result.customElements, ['my-button', 'x-card']);
+});
+
+test.run();
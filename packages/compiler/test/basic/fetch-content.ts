@@ -0,0 +1,29 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `---
+const posts = await Astro.fetchContent('*.md');
+---
+<ul>{posts.map((post) => <li>{post.title}</li>)}</ul>`;
+
+let error: Error;
+test.before(async () => {
+  try {
+    await transform(FIXTURE, {
+      sourcefile: 'Posts.astro',
+    });
+  } catch (err) {
+    error = err;
+  }
+});
+
+test('Astro.fetchContent is an error', () => {
+  assert.ok(error, 'expected transform to fail');
+});
+
+test('error includes the filename and the replacement', () => {
+  assert.match(error.message, 'Posts.astro: Astro.fetchContent() has been removed. Use Astro.glob() or content collections instead.');
+});
+
+test.run();
@@ -0,0 +1,29 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+test('no styles or scripts', async () => {
+  const result = await transform(`<div>Hello world!</div>`);
+  assert.equal(result.hasStyles, false);
+  assert.equal(result.hasScripts, false);
+});
+
+test('styles only', async () => {
+  const result = await transform(`<style>div { color: red; }</style><div>Hello world!</div>`);
+  assert.equal(result.hasStyles, true);
+  assert.equal(result.hasScripts, false);
+});
+
+test('scripts only', async () => {
+  const result = await transform(`<script>console.log("hi")</script><div>Hello world!</div>`);
+  assert.equal(result.hasStyles, false);
+  assert.equal(result.hasScripts, true);
+});
+
+test('inline styles and scripts are not counted', async () => {
+  const result = await transform(`<style is:inline>div { color: red; }</style><script is:inline>console.log("hi")</script><div>Hello world!</div>`);
+  assert.equal(result.hasStyles, false);
+  assert.equal(result.hasScripts, false);
+});
+
+test.run();
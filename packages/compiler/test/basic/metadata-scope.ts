@@ -0,0 +1,10 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+test('$$metadata includes the scope', async () => {
+  const result = await transform(`<style>div { color: red; }</style><div>Hello world!</div>`);
+  assert.match(result.code, `scope: '${result.scope}' });`);
+});
+
+test.run();
@@ -0,0 +1,29 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `
+---
+import Counter from '../components/Counter.jsx';
+import type { Props } from '../types';
+import '../styles/global.css';
+import data from './data.json' with { type: 'json' };
+---
+<Counter />
+`;
+
+let result;
+test.before(async () => {
+  result = await transform(FIXTURE);
+});
+
+test('reports every import in source order', () => {
+  assert.equal(result.modules, [
+    { specifier: '../components/Counter.jsx', attributes: {}, isCssImport: false, isTypeOnly: false },
+    { specifier: '../types', attributes: {}, isCssImport: false, isTypeOnly: true },
+    { specifier: '../styles/global.css', attributes: {}, isCssImport: true, isTypeOnly: false },
+    { specifier: './data.json', attributes: { type: 'json' }, isCssImport: false, isTypeOnly: false },
+  ]);
+});
+
+test.run();
@@ -0,0 +1,25 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `
+---
+import A from '../components/A.jsx';
+import B from '../components/B.vue';
+import C from '../components/C.jsx';
+---
+<A client:only="react" />
+<B client:only="vue" />
+<C client:only="react" />
+`;
+
+let result;
+test.before(async () => {
+  result = await transform(FIXTURE);
+});
+
+test('reports client:only frameworks once each', () => {
+  assert.equal(result.clientOnlyFrameworks, ['react', 'vue']);
+});
+
+test.run();
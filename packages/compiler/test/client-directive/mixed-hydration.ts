@@ -0,0 +1,43 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `---
+import Chart from '../components/Chart.jsx';
+---
+<Chart client:only="react" />
+<Chart />`;
+
+let error: Error;
+test.before(async () => {
+  try {
+    await transform(FIXTURE, {
+      sourcefile: 'Charts.astro',
+    });
+  } catch (err) {
+    error = err;
+  }
+});
+
+test('client:only and server-rendered usage of the same component is an error', () => {
+  assert.ok(error, 'expected transform to fail');
+  assert.match(error.message, 'Charts.astro: <Chart> is used with client:only at 4:');
+});
+
+test('rejection message is the filename and the full message', () => {
+  assert.equal(
+    error.message,
+    'Charts.astro: <Chart> is used with client:only at 4:1 and rendered on the server at 5:1. The import of a client:only component is removed from the server build, so the server-rendered <Chart> will fail.'
+  );
+});
+
+test('other mixed usage still transforms', async () => {
+  const result = await transform(`---
+import Chart from '../components/Chart.jsx';
+---
+<Chart client:load />
+<Chart />`);
+  assert.match(result.code, "$$renderComponent($$result,'Chart',Chart");
+});
+
+test.run();
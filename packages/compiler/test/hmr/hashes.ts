@@ -0,0 +1,68 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE_A = `
+<style>
+  h1 { color: red; }
+</style>
+<script>console.log("a")</script>
+
+<h1>Hello world!</h1>
+`;
+const FIXTURE_B = `
+<style>
+  h1 { color: blue; }
+</style>
+<script>console.log("a")</script>
+
+<h1>Hello world!</h1>
+`;
+const FIXTURE_C = `
+<style>
+  h1 { color: blue; }
+</style>
+<script>console.log("b")</script>
+
+<h1>Hello world!</h1>
+`;
+
+let a, b, c;
+test.before(async () => {
+  [a, b, c] = await Promise.all([FIXTURE_A, FIXTURE_B, FIXTURE_C].map((source) => transform(source, { experimentalStaticExtraction: true })));
+});
+
+test('returns one hash per style and script', () => {
+  assert.equal(a.cssHashes.length, a.css.length);
+  assert.equal(a.scriptHashes.length, a.scripts.length);
+});
+
+test('style-only changes only change cssHashes', () => {
+  assert.not.equal(a.cssHashes[0], b.cssHashes[0]);
+  assert.equal(a.scriptHashes, b.scriptHashes);
+  assert.equal(a.templateHash, b.templateHash);
+});
+
+test('script changes change scriptHashes', () => {
+  assert.not.equal(b.scriptHashes[0], c.scriptHashes[0]);
+  assert.equal(b.templateHash, c.templateHash);
+});
+
+test('template changes change templateHash', async () => {
+  const d = await transform(FIXTURE_C.replace('Hello world!', 'Hello there!'), { experimentalStaticExtraction: true });
+  assert.not.equal(c.templateHash, d.templateHash);
+});
+
+test('templateHash ignores styles and scripts without static extraction', async () => {
+  const [x, y, z] = await Promise.all([FIXTURE_A, FIXTURE_B, FIXTURE_C].map((source) => transform(source)));
+  assert.equal(x.templateHash, y.templateHash);
+  assert.equal(y.templateHash, z.templateHash);
+});
+
+test('hashes are stable across runs', async () => {
+  const again = await transform(FIXTURE_A, { experimentalStaticExtraction: true });
+  assert.equal(again.cssHashes, a.cssHashes);
+  assert.equal(again.templateHash, a.templateHash);
+});
+
+test.run();
@@ -0,0 +1,26 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { parse, transform } from '@astrojs/compiler';
+
+const FIXTURE = `
+---
+let value = 'world';
+---
+
+<h1>Hello {value}</h1>
+`;
+
+test('returns the AST as code', async () => {
+  const result = await transform(FIXTURE, { parseOnly: true });
+  const ast = JSON.parse(result.code);
+  assert.equal(ast.type, 'root');
+  assert.equal(ast.children[0].type, 'frontmatter');
+});
+
+test('matches parse', async () => {
+  const result = await transform(FIXTURE, { parseOnly: true });
+  const { ast } = await parse(FIXTURE);
+  assert.equal(JSON.parse(result.code), ast);
+});
+
+test.run();
@@ -0,0 +1,66 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `<div>Hello world!</div>`;
+const INLINE = '//# sourceMappingURL=data:application/json;charset=utf-8;base64,';
+
+test('no sourcemap by default', async () => {
+  const result = await transform(FIXTURE);
+  assert.equal(result.map, '');
+  assert.not.ok(result.code.includes(INLINE));
+});
+
+test('sourcemap: false', async () => {
+  const result = await transform(FIXTURE, { sourcemap: false });
+  assert.equal(result.map, '');
+  assert.not.ok(result.code.includes(INLINE));
+});
+
+test('sourcemap: true', async () => {
+  const result = await transform(FIXTURE, { sourcemap: true });
+  assert.ok(result.map);
+  assert.ok(result.code.includes(INLINE));
+});
+
+test('sourcemap: both', async () => {
+  const result = await transform(FIXTURE, { sourcemap: 'both' });
+  assert.ok(result.map);
+  assert.ok(result.code.includes(INLINE));
+});
+
+test('sourcemap: inline', async () => {
+  const result = await transform(FIXTURE, { sourcemap: 'inline' });
+  assert.equal(result.map, '');
+  assert.ok(result.code.includes(INLINE));
+});
+
+test('sourcemap: external', async () => {
+  const result = await transform(FIXTURE, { sourcemap: 'external' });
+  assert.ok(result.map);
+  assert.not.ok(result.code.includes(INLINE));
+});
+
+test('unknown sourcemap values are an error', async () => {
+  let error: Error | undefined;
+  try {
+    await transform(FIXTURE, { sourcemap: 'bogus' as any });
+  } catch (err) {
+    error = err;
+  }
+  assert.ok(error, 'expected transform to fail');
+  assert.match(error!.message, 'Invalid sourcemap option "bogus"');
+});
+
+test('non-string sourcemap values name their type', async () => {
+  let error: Error | undefined;
+  try {
+    await transform(FIXTURE, { sourcemap: 1 as any });
+  } catch (err) {
+    error = err;
+  }
+  assert.ok(error, 'expected transform to fail');
+  assert.match(error!.message, 'Invalid sourcemap option of type number');
+});
+
+test.run();
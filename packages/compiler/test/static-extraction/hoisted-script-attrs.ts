@@ -0,0 +1,26 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `
+<script hoist defer data-x="1">console.log('hi');</script>
+<script src="./external.js"></script>
+`;
+
+let result;
+test.before(async () => {
+  result = await transform(FIXTURE, { experimentalStaticExtraction: true });
+});
+
+test('reports the attributes of hoisted scripts', () => {
+  assert.equal(result.scripts.length, 2);
+  const inline = result.scripts.find((script) => script.type === 'inline');
+  assert.equal(inline.attrs, { hoist: '', defer: '', 'data-x': '1' });
+});
+
+test('includes src for external scripts', () => {
+  const external = result.scripts.find((script) => script.type === 'external');
+  assert.equal(external.attrs, { src: './external.js' });
+});
+
+test.run();
@@ -0,0 +1,36 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `
+<style data-kind="a">div { color: red; }</style>
+<style data-kind="b" is:global>p { color: blue; }</style>
+<style data-kind="empty"></style>
+
+<div>Hello world!</div>
+`;
+
+let result;
+test.before(async () => {
+  result = await transform(FIXTURE, {
+    experimentalStaticExtraction: true,
+  });
+});
+
+test('returns attributes for each css entry', () => {
+  assert.equal(result.cssAttrs.length, result.css.length);
+  assert.equal(result.css.length, 2);
+});
+
+test('attributes line up with css', () => {
+  for (let i = 0; i < result.css.length; i++) {
+    if (result.css[i].includes('red')) {
+      assert.equal(result.cssAttrs[i]['data-kind'], 'a');
+    } else {
+      assert.equal(result.cssAttrs[i]['data-kind'], 'b');
+      assert.equal(result.cssAttrs[i]['is:global'], '');
+    }
+  }
+});
+
+test.run();
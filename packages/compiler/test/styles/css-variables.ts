@@ -0,0 +1,21 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `
+<style>div { color: var(--brand); }</style>
+<style is:global>body { background: var(--page); }</style>
+
+<div>Hello world!</div>
+`;
+
+let result;
+test.before(async () => {
+  result = await transform(FIXTURE);
+});
+
+test('reports custom properties referenced by scoped styles', () => {
+  assert.equal(result.cssVariables, ['--brand']);
+});
+
+test.run();
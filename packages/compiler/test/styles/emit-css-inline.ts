@@ -0,0 +1,20 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `<style>div { color: red; }</style><div />`;
+
+test('prints styles inline instead of extracting them', async () => {
+  const result = await transform(FIXTURE, { sourcefile: 'Component.astro', experimentalStaticExtraction: true, emitCSSInline: true });
+  assert.equal(result.css, []);
+  assert.not.match(result.code, '?astro&type=style');
+  assert.match(result.code, /\$\$render`<style>div:where\(\.astro-[^)]+\)\{color:red\}<\/style>/);
+});
+
+test('extracts styles by default', async () => {
+  const result = await transform(FIXTURE, { sourcefile: 'Component.astro', experimentalStaticExtraction: true });
+  assert.equal(result.css.length, 1);
+  assert.not.match(result.code, '<style>');
+});
+
+test.run();
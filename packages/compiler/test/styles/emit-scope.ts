@@ -23,4 +23,9 @@ test('emits a scope', () => {
   assert.ok(result.scope, 'Expected to return a scope');
 });
 
+test('emits a scope class', () => {
+  assert.equal(result.scopeClass, `astro-${result.scope}`);
+  assert.match(result.code, `class="${result.scopeClass}"`);
+});
+
 test.run();
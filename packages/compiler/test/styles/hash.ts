@@ -30,11 +30,23 @@ const FIXTURE_D = `
 
 <script>console.log("Hello world!")</script>
 `;
+const FIXTURE_E = `
+<style>
+	h1 {
+		color: red;
+	}
+</style>
+<h1>
+	Hello world!
+</h1>
+`;
 
 const scopes: string[] = [];
 test.before(async () => {
-  const [{ scope: a }, { scope: b }, { scope: c }, { scope: d }] = await Promise.all([FIXTURE_A, FIXTURE_B, FIXTURE_C, FIXTURE_D].map((source) => transform(source)));
-  scopes.push(a, b, c, d);
+  const [{ scope: a }, { scope: b }, { scope: c }, { scope: d }, { scope: e }] = await Promise.all(
+    [FIXTURE_A, FIXTURE_B, FIXTURE_C, FIXTURE_D, FIXTURE_E].map((source) => transform(source))
+  );
+  scopes.push(a, b, c, d, e);
 });
 
 test('hash is stable when styles change', () => {
@@ -52,4 +64,9 @@ test('hash changes when scripts change', () => {
   assert.not.equal(c, d, 'Expected scopes to not be equal');
 });
 
+test('hash is stable when only whitespace changes without a filename', () => {
+  const [a, , , , e] = scopes;
+  assert.equal(a, e, 'Expected scopes to be equal');
+});
+
 test.run();
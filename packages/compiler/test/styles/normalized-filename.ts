@@ -0,0 +1,31 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `<style>div { color: red; }</style><div />`;
+
+test('derives the style import from root', async () => {
+  const result = await transform(FIXTURE, {
+    sourcefile: '/home/user/site/src/pages/index.astro',
+    root: '/home/user/site',
+    experimentalStaticExtraction: true,
+  });
+  assert.match(result.code, 'import "/src/pages/index.astro?astro&type=style&index=0&lang.css";');
+});
+
+test('prefers an explicit normalizedFilename', async () => {
+  const result = await transform(FIXTURE, {
+    sourcefile: '/home/user/site/src/pages/index.astro',
+    root: '/home/user/site',
+    normalizedFilename: '/pages/index.astro',
+    experimentalStaticExtraction: true,
+  });
+  assert.match(result.code, 'import "/pages/index.astro?astro&type=style&index=0&lang.css";');
+});
+
+test('keeps sourcefile without root', async () => {
+  const result = await transform(FIXTURE, { sourcefile: '/home/user/site/src/pages/index.astro', experimentalStaticExtraction: true });
+  assert.match(result.code, 'import "/home/user/site/src/pages/index.astro?astro&type=style&index=0&lang.css";');
+});
+
+test.run();
@@ -0,0 +1,27 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `<style lang="scss">div { color: red; }</style><div />`;
+
+test('defaults to the style lang', async () => {
+  const result = await transform(FIXTURE, { sourcefile: 'Component.astro', experimentalStaticExtraction: true });
+  assert.match(result.code, 'import "Component.astro?astro&type=style&index=0&lang.scss";');
+});
+
+test('defaults to css without a lang', async () => {
+  const result = await transform(`<style>div { color: red; }</style><div />`, { sourcefile: 'Component.astro', experimentalStaticExtraction: true });
+  assert.match(result.code, 'import "Component.astro?astro&type=style&index=0&lang.css";');
+});
+
+test('uses the given extension', async () => {
+  const result = await transform(FIXTURE, { sourcefile: 'Component.astro', experimentalStaticExtraction: true, outputExtension: 'css' });
+  assert.match(result.code, 'import "Component.astro?astro&type=style&index=0&lang.css";');
+});
+
+test('ignores an empty extension', async () => {
+  const result = await transform(FIXTURE, { sourcefile: 'Component.astro', experimentalStaticExtraction: true, outputExtension: '' });
+  assert.match(result.code, 'import "Component.astro?astro&type=style&index=0&lang.scss";');
+});
+
+test.run();
@@ -0,0 +1,68 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transform } from '@astrojs/compiler';
+
+const FIXTURE = `
+---
+const color = 'red';
+---
+<style define:vars={{ color }}>div { color: var(--color); }</style>
+<style is:global>p { color: blue; }</style>
+
+<div>Hello world!</div>
+`;
+
+let result;
+test.before(async () => {
+  result = await transform(FIXTURE, {
+    experimentalStaticExtraction: true,
+  });
+});
+
+test('returns a style block for each css entry', () => {
+  assert.equal(result.styleBlocks.length, result.css.length);
+});
+
+test('style blocks line up with css', () => {
+  for (let i = 0; i < result.css.length; i++) {
+    const { content, ...block } = result.styleBlocks[i];
+    assert.equal(content, result.css[i]);
+    if (result.css[i].includes('blue')) {
+      assert.equal(block, { lang: '', isGlobal: true, hasDefineVars: false, defineVarsSource: '', preprocessor: '' });
+    } else {
+      assert.equal(block, { lang: '', isGlobal: false, hasDefineVars: true, defineVarsSource: '{ color }', preprocessor: '' });
+    }
+  }
+});
+
+test('style blocks describe global and scoped lang blocks', async () => {
+  const { css, styleBlocks } = await transform(`<style lang="scss">div { color: red; }</style><style lang="scss" is:global>p { color: blue; }</style><div />`, {
+    experimentalStaticExtraction: true,
+  });
+  assert.equal(styleBlocks.length, 2);
+  assert.equal(
+    styleBlocks.map((block) => block.content),
+    css
+  );
+  const scoped = styleBlocks.find((block) => block.content.includes('red'));
+  assert.match(scoped.content, 'astro-');
+  assert.equal(scoped.lang, 'scss');
+  assert.equal(scoped.isGlobal, false);
+  const global = styleBlocks.find((block) => block.content.includes('blue'));
+  assert.not.match(global.content, 'astro-');
+  assert.equal(global.lang, 'scss');
+  assert.equal(global.isGlobal, true);
+});
+
+test('looks up the preprocessor for each lang', async () => {
+  const { styleBlocks } = await transform(`<style lang="scss">div { color: red; }</style><style lang="stylus">p { color: blue; }</style><div />`, {
+    experimentalStaticExtraction: true,
+    stylePreprocessorMap: { scss: 'sass', less: 'less' },
+  });
+  const scss = styleBlocks.find((block) => block.content.includes('red'));
+  const stylus = styleBlocks.find((block) => block.content.includes('blue'));
+  assert.equal(scss.preprocessor, 'sass');
+  assert.equal(stylus.preprocessor, '');
+});
+
+test.run();
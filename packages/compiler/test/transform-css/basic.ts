@@ -0,0 +1,49 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+import { transformCSS } from '@astrojs/compiler';
+
+const FIXTURE = `.title { color: red; } :global(body) .title { color: blue; }`;
+
+test('scopes with the :where() strategy by default', async () => {
+  const result = await transformCSS(FIXTURE, { scope: 'XXXXXX' });
+  assert.equal(result.code, `.title:where(.astro-XXXXXX){color:red}body .title:where(.astro-XXXXXX){color:blue}`);
+  assert.equal(result.errors.length, 0);
+});
+
+test('scopes with the where strategy', async () => {
+  const result = await transformCSS(FIXTURE, { scope: 'XXXXXX', strategy: 'where' });
+  assert.equal(result.code, `.title:where(.astro-XXXXXX){color:red}body .title:where(.astro-XXXXXX){color:blue}`);
+});
+
+test('scopes with the class strategy', async () => {
+  const result = await transformCSS(FIXTURE, { scope: 'XXXXXX', strategy: 'class' });
+  assert.equal(result.code, `.title.astro-XXXXXX{color:red}body .title.astro-XXXXXX{color:blue}`);
+});
+
+test('scopes with the attribute strategy', async () => {
+  const result = await transformCSS(FIXTURE, { scope: 'XXXXXX', strategy: 'attribute' });
+  assert.equal(result.code, `.title[data-astro-cid-XXXXXX]{color:red}body .title[data-astro-cid-XXXXXX]{color:blue}`);
+});
+
+test('returns the scope class for each strategy', async () => {
+  for (const strategy of ['where', 'class', 'attribute'] as const) {
+    const result = await transformCSS(FIXTURE, { scope: 'XXXXXX', strategy });
+    assert.ok(result.code.includes(result.scopeClass), `Expected ${strategy} selectors to use ${result.scopeClass}`);
+  }
+  const result = await transformCSS(FIXTURE, { scope: 'XXXXXX', strategy: 'attribute' });
+  assert.equal(result.scopeClass, 'data-astro-cid-XXXXXX');
+});
+
+test('reports unknown strategies', async () => {
+  const result = await transformCSS(FIXTURE, { scope: 'XXXXXX', strategy: 'classes' as any });
+  assert.equal(result.errors.length, 1);
+  assert.match(result.errors[0], 'Unknown scope strategy "classes"');
+  assert.equal(result.scopeClass, 'astro-XXXXXX');
+});
+
+test('reports errors', async () => {
+  const result = await transformCSS(`.title { color: red;`, { scope: 'XXXXXX' });
+  assert.equal(result.errors.length, 1);
+});
+
+test.run();
@@ -72,4 +72,16 @@ export default function __AstroComponent_(_props: Record<string, any>): any {}
   assert.snapshot(code, output, `expected code to match snapshot`);
 });
 
+test('keeps set:slots as an attribute', async () => {
+  const input = `<Component set:slots={$$slots}><div slot="a">A</div></Component>`;
+  const output = `<Fragment>
+<Component set:slots={$$slots}><div slot="a">A</div></Component>
+</Fragment>
+
+export default function __AstroComponent_(_props: Record<string, any>): any {}
+`;
+  const { code } = await convertToTSX(input);
+  assert.snapshot(code, output, `expected code to match snapshot`);
+});
+
 test.run();